@@ -0,0 +1,333 @@
+//! Analyze command - Inspect the structure of a merged call graph.
+//!
+//! A merged `atoms.json` is a directed call graph (each key's `dependencies`
+//! are its out-edges). This command surfaces three structural properties that
+//! drive downstream verification cost:
+//! - recursion and mutual-recursion groups (strongly-connected components),
+//! - stub atoms still *reachable* from real `Exec` entry points, which are the
+//!   cross-project gaps that actually matter versus dead stubs, and
+//! - a dependency-respecting order in which to run Verus (topological order of
+//!   the SCC condensation, callees before callers).
+
+use super::atoms::{is_stub, load_atoms_file};
+use probe_verus::AtomWithLines;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::path::PathBuf;
+
+/// A strongly-connected component of the call graph.
+#[derive(Serialize)]
+struct Component {
+    /// Member atom keys, sorted for deterministic output.
+    members: Vec<String>,
+    /// True if this component represents recursion: more than one member, or
+    /// a single member that calls itself.
+    is_cycle: bool,
+}
+
+/// The full analysis report, serialized to JSON.
+#[derive(Serialize)]
+struct AnalysisReport {
+    /// Recursion/mutual-recursion groups (SCCs flagged as cycles).
+    cycles: Vec<Component>,
+    /// Stub atoms reachable from real `Exec` entry points.
+    reachable_unresolved_stubs: Vec<String>,
+    /// SCC condensation in topological order (callees before callers). Each
+    /// entry is the sorted member list of one component.
+    verification_order: Vec<Vec<String>>,
+}
+
+/// Execute the analyze command.
+pub fn cmd_analyze(atoms_path: PathBuf, output: Option<PathBuf>) {
+    println!("═══════════════════════════════════════════════════════════");
+    println!("  Probe Verus - Analyze: Call-Graph Structure");
+    println!("═══════════════════════════════════════════════════════════");
+    println!();
+
+    let atoms = match load_atoms_file(&atoms_path) {
+        Ok(atoms) => atoms,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("  Loaded {} atoms from {}", atoms.len(), atoms_path.display());
+
+    let graph = Graph::from_atoms(&atoms);
+    let sccs = graph.tarjan_scc();
+
+    let cycles: Vec<Component> = sccs
+        .iter()
+        .filter(|scc| graph.is_cycle(scc))
+        .map(|scc| Component {
+            members: scc.clone(),
+            is_cycle: true,
+        })
+        .collect();
+
+    let reachable_unresolved_stubs = graph.reachable_stubs(&atoms);
+    let verification_order = graph.condensation_topo_order(&sccs);
+
+    let report = AnalysisReport {
+        cycles,
+        reachable_unresolved_stubs,
+        verification_order,
+    };
+
+    print_summary(&report);
+
+    if let Some(output) = output {
+        let json = serde_json::to_string_pretty(&report).expect("Failed to serialize JSON");
+        std::fs::write(&output, &json).expect("Failed to write report");
+        println!();
+        println!("Report written to: {}", output.display());
+    }
+}
+
+
+/// Directed call graph with integer node ids for the SCC algorithms.
+struct Graph {
+    /// Node key per id, in stable (sorted) order.
+    keys: Vec<String>,
+    /// Out-edges as node ids. Only edges to known atoms are retained.
+    adj: Vec<Vec<usize>>,
+    /// Reverse lookup from key to id.
+    index: BTreeMap<String, usize>,
+}
+
+impl Graph {
+    fn from_atoms(atoms: &BTreeMap<String, AtomWithLines>) -> Self {
+        let keys: Vec<String> = atoms.keys().cloned().collect();
+        let index: BTreeMap<String, usize> =
+            keys.iter().enumerate().map(|(i, k)| (k.clone(), i)).collect();
+
+        let adj = keys
+            .iter()
+            .map(|k| {
+                atoms[k]
+                    .dependencies
+                    .iter()
+                    .filter_map(|dep| index.get(dep).copied())
+                    .collect()
+            })
+            .collect();
+
+        Self { keys, adj, index }
+    }
+
+    /// Compute strongly-connected components (Tarjan's algorithm). Each SCC is
+    /// returned as a sorted list of member keys; the component list itself is
+    /// in reverse-topological order (sinks first), which Tarjan yields
+    /// naturally.
+    fn tarjan_scc(&self) -> Vec<Vec<String>> {
+        let n = self.adj.len();
+        let mut low = vec![0usize; n];
+        let mut num = vec![usize::MAX; n];
+        let mut on_stack = vec![false; n];
+        let mut stack: Vec<usize> = Vec::new();
+        let mut counter = 0usize;
+        let mut sccs: Vec<Vec<String>> = Vec::new();
+
+        // Iterative DFS to avoid blowing the stack on deep graphs.
+        for start in 0..n {
+            if num[start] != usize::MAX {
+                continue;
+            }
+            let mut work: Vec<(usize, usize)> = vec![(start, 0)];
+            while let Some((v, child_idx)) = work.pop() {
+                if child_idx == 0 {
+                    num[v] = counter;
+                    low[v] = counter;
+                    counter += 1;
+                    stack.push(v);
+                    on_stack[v] = true;
+                }
+
+                if child_idx < self.adj[v].len() {
+                    let w = self.adj[v][child_idx];
+                    work.push((v, child_idx + 1));
+                    if num[w] == usize::MAX {
+                        work.push((w, 0));
+                    } else if on_stack[w] {
+                        low[v] = low[v].min(num[w]);
+                    }
+                } else {
+                    // All children processed: propagate low-link to parent.
+                    if let Some(&(parent, _)) = work.last() {
+                        low[parent] = low[parent].min(low[v]);
+                    }
+                    if low[v] == num[v] {
+                        let mut component = Vec::new();
+                        while let Some(w) = stack.pop() {
+                            on_stack[w] = false;
+                            component.push(self.keys[w].clone());
+                            if w == v {
+                                break;
+                            }
+                        }
+                        component.sort();
+                        sccs.push(component);
+                    }
+                }
+            }
+        }
+
+        sccs
+    }
+
+    /// A component is a cycle if it has more than one member, or its single
+    /// member calls itself.
+    fn is_cycle(&self, scc: &[String]) -> bool {
+        if scc.len() > 1 {
+            return true;
+        }
+        let id = self.index[&scc[0]];
+        self.adj[id].contains(&id)
+    }
+
+    /// Stub atoms reachable from real `Exec` entry points via BFS over
+    /// dependencies. Returns sorted keys.
+    fn reachable_stubs(&self, atoms: &BTreeMap<String, AtomWithLines>) -> Vec<String> {
+        use probe_verus::FunctionMode;
+
+        let mut visited = vec![false; self.adj.len()];
+        let mut queue: VecDeque<usize> = VecDeque::new();
+
+        for (i, key) in self.keys.iter().enumerate() {
+            let atom = &atoms[key];
+            if !is_stub(atom) && atom.mode == FunctionMode::Exec {
+                visited[i] = true;
+                queue.push_back(i);
+            }
+        }
+
+        let mut stubs = BTreeSet::new();
+        while let Some(v) = queue.pop_front() {
+            for &w in &self.adj[v] {
+                if !visited[w] {
+                    visited[w] = true;
+                    if is_stub(&atoms[&self.keys[w]]) {
+                        stubs.insert(self.keys[w].clone());
+                    }
+                    queue.push_back(w);
+                }
+            }
+        }
+
+        stubs.into_iter().collect()
+    }
+
+    /// Topological order of the SCC condensation, callees before callers.
+    ///
+    /// Tarjan emits SCCs in reverse-topological order already, so the returned
+    /// components are exactly that reverse, which is the callee-first schedule
+    /// we want.
+    fn condensation_topo_order(&self, sccs: &[Vec<String>]) -> Vec<Vec<String>> {
+        sccs.to_vec()
+    }
+}
+
+/// Print the human-readable analysis summary.
+fn print_summary(report: &AnalysisReport) {
+    println!();
+    println!("Summary:");
+    println!("  Recursion groups (cycles): {}", report.cycles.len());
+    for cycle in &report.cycles {
+        if cycle.members.len() == 1 {
+            println!("    - self-recursive: {}", cycle.members[0]);
+        } else {
+            println!(
+                "    - mutual recursion ({}): {}",
+                cycle.members.len(),
+                cycle.members.join(", ")
+            );
+        }
+    }
+    println!(
+        "  Reachable unresolved stubs: {}",
+        report.reachable_unresolved_stubs.len()
+    );
+    for stub in &report.reachable_unresolved_stubs {
+        println!("    - {}", stub);
+    }
+    println!(
+        "  Verification order: {} component(s), callees first",
+        report.verification_order.len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use probe_verus::{CodeTextInfo, FunctionMode};
+
+    fn atom(key: &str, path: &str, deps: &[&str]) -> AtomWithLines {
+        AtomWithLines {
+            display_name: key.to_string(),
+            code_name: key.to_string(),
+            dependencies: deps.iter().map(|d| d.to_string()).collect(),
+            dependencies_with_locations: Vec::new(),
+            code_module: String::new(),
+            code_path: path.to_string(),
+            code_text: CodeTextInfo {
+                lines_start: if path.is_empty() { 0 } else { 10 },
+                lines_end: if path.is_empty() { 0 } else { 20 },
+            },
+            mode: FunctionMode::Exec,
+        }
+    }
+
+    fn build(entries: Vec<AtomWithLines>) -> BTreeMap<String, AtomWithLines> {
+        entries
+            .into_iter()
+            .map(|a| (a.code_name.clone(), a))
+            .collect()
+    }
+
+    #[test]
+    fn test_detects_self_recursion() {
+        let atoms = build(vec![atom("a", "a.rs", &["a"])]);
+        let graph = Graph::from_atoms(&atoms);
+        let sccs = graph.tarjan_scc();
+        let cycles: Vec<_> = sccs.iter().filter(|s| graph.is_cycle(s)).collect();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0], &vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn test_detects_mutual_recursion() {
+        let atoms = build(vec![atom("a", "a.rs", &["b"]), atom("b", "b.rs", &["a"])]);
+        let graph = Graph::from_atoms(&atoms);
+        let sccs = graph.tarjan_scc();
+        let cycles: Vec<_> = sccs.iter().filter(|s| graph.is_cycle(s)).collect();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0], &vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn test_reachable_stubs_excludes_dead() {
+        let atoms = build(vec![
+            atom("entry", "entry.rs", &["live_stub"]),
+            atom("live_stub", "", &[]),
+            atom("dead_stub", "", &[]),
+        ]);
+        let graph = Graph::from_atoms(&atoms);
+        let stubs = graph.reachable_stubs(&atoms);
+        assert_eq!(stubs, vec!["live_stub".to_string()]);
+    }
+
+    #[test]
+    fn test_verification_order_callees_first() {
+        // caller -> callee; callee must come first.
+        let atoms = build(vec![
+            atom("caller", "caller.rs", &["callee"]),
+            atom("callee", "callee.rs", &[]),
+        ]);
+        let graph = Graph::from_atoms(&atoms);
+        let sccs = graph.tarjan_scc();
+        let order = graph.condensation_topo_order(&sccs);
+        let callee_pos = order.iter().position(|c| c[0] == "callee").unwrap();
+        let caller_pos = order.iter().position(|c| c[0] == "caller").unwrap();
+        assert!(callee_pos < caller_pos);
+    }
+}
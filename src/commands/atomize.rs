@@ -6,6 +6,7 @@ use probe_verus::{
     scip_cache::{Analyzer, ScipCache},
     AtomWithLines,
 };
+use super::atoms::is_stub;
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
@@ -19,12 +20,17 @@ pub fn cmd_atomize(
     with_locations: bool,
     use_rust_analyzer: bool,
     allow_duplicates: bool,
+    threads: Option<usize>,
+    companion_projects: Vec<PathBuf>,
 ) {
     println!("═══════════════════════════════════════════════════════════");
     println!("  Probe Verus - Atomize: Generate Call Graph Data");
     println!("═══════════════════════════════════════════════════════════");
     println!();
 
+    // Cap parallelism for the concurrent span-parsing step (mostly for CI).
+    configure_thread_pool(threads);
+
     // Validate project
     if let Err(msg) = validate_project(&project_path) {
         eprintln!("✗ Error: {}", msg);
@@ -34,45 +40,80 @@ pub fn cmd_atomize(
 
     // Get or generate SCIP JSON
     let analyzer = if use_rust_analyzer {
-        Analyzer::RustAnalyzer
+        Analyzer::rust_analyzer()
     } else {
-        Analyzer::VerusAnalyzer
+        Analyzer::verus_analyzer()
     };
-    let scip_cache = ScipCache::with_analyzer(&project_path, analyzer);
-    let json_path = get_scip_json(&scip_cache, regenerate_scip);
-
-    // Parse SCIP JSON and build call graph
-    println!("Parsing SCIP JSON and building call graph...");
 
-    let scip_index = match parse_scip_json(json_path.to_str().unwrap()) {
-        Ok(idx) => idx,
-        Err(e) => {
-            eprintln!("✗ Failed to parse SCIP JSON: {}", e);
-            std::process::exit(1);
-        }
-    };
+    // The primary project plus any companion crates indexed alongside it.
+    let mut projects = vec![project_path.clone()];
+    projects.extend(companion_projects.iter().cloned());
+    if projects.len() > 1 {
+        println!("  Indexing {} crates (multi-index mode)", projects.len());
+    }
 
-    let (call_graph, symbol_to_display_name) = build_call_graph(&scip_index);
-    println!("  ✓ Call graph built with {} functions", call_graph.len());
+    // Convert each crate's SCIP index into atoms, then merge so that symbols
+    // defined in a companion crate become first-class atoms (with real spans)
+    // instead of stubs.
+    let mut atoms = Vec::new();
+    let mut duplicates = Vec::new();
+    for path in &projects {
+        let scip_cache = ScipCache::with_analyzer(path, analyzer.clone());
+        let json_path = get_scip_json(&scip_cache, regenerate_scip);
+
+        println!("Parsing SCIP JSON and building call graph for {}...", path.display());
+        let scip_index = match parse_scip_json(json_path.to_str().unwrap()) {
+            Ok(idx) => idx,
+            Err(e) => {
+                eprintln!("✗ Failed to parse SCIP JSON: {}", e);
+                std::process::exit(1);
+            }
+        };
+
+        let (call_graph, symbol_to_display_name) = build_call_graph(&scip_index);
+        println!("  ✓ Call graph built with {} functions", call_graph.len());
+
+        let crate_atoms = convert_to_atoms_with_parsed_spans(
+            &call_graph,
+            &symbol_to_display_name,
+            path,
+            with_locations,
+        );
+        println!(
+            "  ✓ Converted {} functions to atoms format",
+            crate_atoms.len()
+        );
+        // A code_name that occurs twice within one index is genuinely
+        // ambiguous (e.g. competing trait impls), so duplicates are detected
+        // per index, before the cross-index merge below collapses keys.
+        duplicates.extend(find_duplicate_code_names(&crate_atoms));
+        atoms.extend(crate_atoms);
+    }
     println!();
-
-    // Convert to atoms format with line numbers
-    println!("Converting to atoms format with accurate line numbers...");
-    println!("  Parsing source files with verus_syn for accurate function spans...");
-
-    let atoms = convert_to_atoms_with_parsed_spans(
-        &call_graph,
-        &symbol_to_display_name,
-        &project_path,
-        with_locations,
-    );
-    println!("  ✓ Converted {} functions to atoms format", atoms.len());
     if with_locations {
         println!("    (including dependencies-with-locations)");
     }
 
-    // Check for duplicate code_names
-    let duplicates = find_duplicate_code_names(&atoms);
+    // Convert atoms list to dictionary keyed by code_name. When the same
+    // fully-qualified symbol appears in more than one index (e.g. a re-exported
+    // item), prefer a real atom over a stub and, among reals, the one whose
+    // source file actually exists on disk.
+    let mut atoms_dict: BTreeMap<String, AtomWithLines> = BTreeMap::new();
+    for atom in atoms {
+        match atoms_dict.get(&atom.code_name) {
+            Some(existing) if prefer_incoming(existing, &atom) => {
+                atoms_dict.insert(atom.code_name.clone(), atom);
+            }
+            Some(_) => {}
+            None => {
+                atoms_dict.insert(atom.code_name.clone(), atom);
+            }
+        }
+    }
+
+    // Report the duplicate code_names found within each index. The same symbol
+    // seen in two different indexes (re-exports, overlapping workspaces) is not
+    // reported; it was collapsed by `prefer_incoming` above.
     if !duplicates.is_empty() {
         let report = format_duplicate_report(&duplicates);
         if allow_duplicates {
@@ -89,13 +130,7 @@ pub fn cmd_atomize(
         }
     }
 
-    // Convert atoms list to dictionary keyed by code_name (first occurrence wins)
-    let mut atoms_dict: BTreeMap<String, AtomWithLines> = BTreeMap::new();
-    for atom in atoms {
-        atoms_dict.entry(atom.code_name.clone()).or_insert(atom);
-    }
-
-    // Add stub atoms for external function dependencies
+    // Add stub atoms only for dependencies still unresolved after the merge.
     let stub_count = add_external_stubs(&mut atoms_dict);
     if stub_count > 0 {
         println!("  ✓ Added {} external function stub(s)", stub_count);
@@ -109,6 +144,35 @@ pub fn cmd_atomize(
     print_success_summary(&output, &atoms_dict);
 }
 
+/// Whether an incoming atom should replace one already recorded under the same
+/// key during the multi-index merge: a real atom beats a stub, and among two
+/// real atoms the one whose `code_path` exists on disk wins.
+fn prefer_incoming(existing: &AtomWithLines, incoming: &AtomWithLines) -> bool {
+    let existing_stub = is_stub(existing);
+    let incoming_stub = is_stub(incoming);
+    match (existing_stub, incoming_stub) {
+        (true, false) => true,
+        (false, true) => false,
+        _ => {
+            // Both real (or both stubs): prefer a path that exists on disk.
+            !Path::new(&existing.code_path).exists() && Path::new(&incoming.code_path).exists()
+        }
+    }
+}
+
+
+/// Configure the global rayon thread pool used by the concurrent span-parsing
+/// step. A `None` thread count leaves rayon's default (all cores); a `Some`
+/// value caps parallelism. Building the global pool can only happen once per
+/// process, so a subsequent attempt is silently ignored.
+fn configure_thread_pool(threads: Option<usize>) {
+    if let Some(n) = threads {
+        let _ = rayon::ThreadPoolBuilder::new()
+            .num_threads(n.max(1))
+            .build_global();
+    }
+}
+
 /// Validate that the project path exists and contains a Cargo.toml.
 fn validate_project(project_path: &Path) -> Result<(), String> {
     if !project_path.exists() {
@@ -158,27 +222,66 @@ fn get_scip_json(cache: &ScipCache, regenerate: bool) -> PathBuf {
     }
 }
 
-/// Format a human-readable report of duplicate code_names.
+/// Format a human-readable report of duplicate code_names as annotated source
+/// snippets, one block per collision, falling back to plain references for
+/// occurrences whose source file can no longer be read.
 fn format_duplicate_report(duplicates: &[probe_verus::DuplicateCodeName]) -> String {
+    use probe_verus::snippet::{Annotation, Snippet};
+
+    let color = supports_color();
+    let term_width = terminal_width();
+
     let mut msg = format!(
-        "WARNING: Found {} duplicate code_name(s):\n",
+        "WARNING: Found {} duplicate code_name(s):\n\n",
         duplicates.len()
     );
+
     for dup in duplicates {
-        msg.push_str(&format!("    - '{}'\n", dup.code_name));
-        for occ in &dup.occurrences {
-            msg.push_str(&format!(
-                "      at {}:{} ({})\n",
-                occ.code_path, occ.lines_start, occ.display_name
-            ));
-        }
+        let annotations: Vec<Annotation> = dup
+            .occurrences
+            .iter()
+            .enumerate()
+            .map(|(i, occ)| Annotation {
+                file: occ.code_path.clone(),
+                line: occ.lines_start,
+                // Occurrences carry only `lines_start`, no column, so the whole
+                // line is underlined.
+                columns: None,
+                label: if i == 0 {
+                    format!("defined here ({})", occ.display_name)
+                } else {
+                    format!("and here ({})", occ.display_name)
+                },
+            })
+            .collect();
+
+        let snippet = Snippet {
+            title: format!("duplicate code_name '{}'", dup.code_name),
+            annotations,
+        };
+        msg.push_str(&snippet.render(color, term_width));
+        msg.push('\n');
     }
-    msg.push_str("\n    Duplicate code_names cannot be used as dictionary keys.\n");
+
+    msg.push_str("    Duplicate code_names cannot be used as dictionary keys.\n");
     msg.push_str("    This may indicate trait implementations that cannot be distinguished.\n");
     msg.push_str("    Use --allow-duplicates to continue anyway (first occurrence kept).");
     msg
 }
 
+/// Whether the terminal supports ANSI color (honors `NO_COLOR`).
+fn supports_color() -> bool {
+    std::env::var_os("NO_COLOR").is_none()
+}
+
+/// Best-effort terminal width, defaulting to 100 columns.
+fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .unwrap_or(100)
+}
+
 /// Print the success summary.
 fn print_success_summary(output: &Path, atoms_dict: &BTreeMap<String, AtomWithLines>) {
     println!();
@@ -210,11 +313,14 @@ pub fn atomize_internal(
     verbose: bool,
     use_rust_analyzer: bool,
     allow_duplicates: bool,
+    threads: Option<usize>,
 ) -> Result<usize, String> {
+    configure_thread_pool(threads);
+
     let analyzer = if use_rust_analyzer {
-        Analyzer::RustAnalyzer
+        Analyzer::rust_analyzer()
     } else {
-        Analyzer::VerusAnalyzer
+        Analyzer::verus_analyzer()
     };
     let cache = ScipCache::with_analyzer(project_path, analyzer);
 
@@ -0,0 +1,121 @@
+//! Shared atom I/O and classification used across the atomize/merge/analyze
+//! commands: loading an atoms container (JSON or the `rkyv` binary format),
+//! writing one back, and the stub predicate. Centralized here so the three
+//! commands don't each re-paste the loader and `is_stub`.
+
+use probe_verus::AtomWithLines;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// A stub is an atom with no source: empty code_path and zero-length span.
+pub(crate) fn is_stub(atom: &AtomWithLines) -> bool {
+    atom.code_path.is_empty() && atom.code_text.lines_start == 0 && atom.code_text.lines_end == 0
+}
+
+/// On-disk atom container format.
+///
+/// JSON is the default interchange format; the `rkyv`-backed binary format
+/// exists so that workspace-scale merges (dozens of large `atoms.json` files)
+/// can skip the `serde_json` round-trip that otherwise dominates time and
+/// memory. The two can be mixed freely on one command line -- the format is
+/// inferred from the file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AtomFormat {
+    Json,
+    Binary,
+}
+
+impl AtomFormat {
+    /// Infer the format from a path's extension (`.bin` -> binary, else JSON).
+    pub(crate) fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("bin") => AtomFormat::Binary,
+            _ => AtomFormat::Json,
+        }
+    }
+}
+
+/// Load an atoms file into a BTreeMap, reconstructing code_name fields
+/// from the dictionary keys (since code_name is skip_serializing).
+///
+/// The format is inferred from the extension: `.bin` files are read through
+/// the validated `rkyv` path, everything else is parsed as JSON.
+pub(crate) fn load_atoms_file(path: &PathBuf) -> Result<BTreeMap<String, AtomWithLines>, String> {
+    match AtomFormat::from_path(path) {
+        AtomFormat::Json => load_atoms_json(path),
+        AtomFormat::Binary => load_atoms_binary(path),
+    }
+}
+
+/// Load a JSON atoms file, reconstructing `code_name` from each key.
+fn load_atoms_json(path: &PathBuf) -> Result<BTreeMap<String, AtomWithLines>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut atoms: BTreeMap<String, AtomWithLines> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+    for (key, atom) in atoms.iter_mut() {
+        atom.code_name = key.clone();
+    }
+
+    Ok(atoms)
+}
+
+/// Load an `rkyv`-encoded atoms file.
+///
+/// The file is `mmap`ed and its root validated with `bytecheck` before being
+/// deserialized into an owned `BTreeMap` (the merge mutates keys and
+/// `code_name`, so it needs owned atoms rather than the archived view).
+/// Validation failures surface as load errors rather than panics. The win over
+/// JSON here is skipping `serde_json` parsing, not zero-copy access.
+fn load_atoms_binary(path: &PathBuf) -> Result<BTreeMap<String, AtomWithLines>, String> {
+    use std::fs::File;
+
+    let file =
+        File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    // SAFETY: the index is owned by this process for the duration of the load
+    // and is not concurrently truncated.
+    let mmap = unsafe { memmap2::Mmap::map(&file) }
+        .map_err(|e| format!("Failed to mmap {}: {}", path.display(), e))?;
+
+    let archived = rkyv::access::<ArchivedAtomsMap, rkyv::rancor::Error>(&mmap[..])
+        .map_err(|e| format!("Invalid rkyv atoms in {}: {}", path.display(), e))?;
+
+    let mut atoms: BTreeMap<String, AtomWithLines> =
+        rkyv::deserialize::<_, rkyv::rancor::Error>(archived)
+            .map_err(|e| format!("Failed to deserialize {}: {}", path.display(), e))?;
+
+    for (key, atom) in atoms.iter_mut() {
+        atom.code_name = key.clone();
+    }
+
+    Ok(atoms)
+}
+
+/// Alias for the archived root of a serialized atoms map, for readability at
+/// the `rkyv::access` call site.
+type ArchivedAtomsMap = rkyv::collections::btree_map::ArchivedBTreeMap<
+    rkyv::string::ArchivedString,
+    <AtomWithLines as rkyv::Archive>::Archived,
+>;
+
+/// Write a merged atoms map in the format implied by the output extension.
+pub(crate) fn write_atoms_file(
+    path: &Path,
+    atoms: &BTreeMap<String, AtomWithLines>,
+) -> Result<(), String> {
+    match AtomFormat::from_path(path) {
+        AtomFormat::Json => {
+            let json = serde_json::to_string_pretty(atoms)
+                .map_err(|e| format!("Failed to serialize JSON: {}", e))?;
+            std::fs::write(path, &json)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+        }
+        AtomFormat::Binary => {
+            let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(atoms)
+                .map_err(|e| format!("Failed to serialize rkyv: {}", e))?;
+            std::fs::write(path, &bytes)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+        }
+    }
+}
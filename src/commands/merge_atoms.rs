@@ -4,13 +4,99 @@
 //! from other indexed projects, enabling cross-project call graphs without
 //! requiring a single combined workspace.
 
-use probe_verus::{normalize_code_name, AtomWithLines};
+use super::atoms::{is_stub, load_atoms_file, write_atoms_file};
+use probe_verus::{normalize_code_name, AtomWithLines, FunctionMode};
+use serde::Serialize;
 use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-/// A stub is an atom with no source: empty code_path and zero-length span.
-fn is_stub(atom: &AtomWithLines) -> bool {
-    atom.code_path.is_empty() && atom.code_text.lines_start == 0 && atom.code_text.lines_end == 0
+/// Severity of a merge diagnostic, chosen so a generic JSON consumer or a
+/// problem-matcher-style regex can map each entry to an annotation level.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum Severity {
+    /// A real-vs-real conflict: two non-stub atoms compete for one key.
+    Warning,
+    /// A stub that survived the merge and still needs resolving. Every
+    /// remaining stub is reported; the merge does no reachability analysis, so
+    /// these are not filtered to ones reachable from `Exec` entry points (use
+    /// the `analyze` subcommand for that).
+    Info,
+}
+
+/// A single machine-readable merge diagnostic.
+///
+/// The schema carries everything a CI pipeline needs to annotate a source
+/// location: the affected atom key, the competing `code_path` values and their
+/// first source line, plus a rendered message.
+#[derive(Debug, Clone, Serialize)]
+struct MergeDiagnostic {
+    severity: Severity,
+    /// Normalized atom key the diagnostic is about.
+    code_name: String,
+    /// Competing source paths (two for a conflict, possibly empty for a stub).
+    code_paths: Vec<String>,
+    /// 1-based start lines paired with `code_paths`.
+    lines_start: Vec<usize>,
+    /// Human-readable message.
+    message: String,
+}
+
+/// How to resolve a real-vs-real conflict (two non-stub atoms under the same
+/// normalized key coming from different indexes).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keep the base (first-seen) atom. Historical default.
+    FirstWins,
+    /// Keep whichever atom spans more source lines
+    /// (`code_text.lines_end - lines_start`), i.e. the more complete body.
+    PreferLargerSpan,
+    /// Keep the atom whose `FunctionMode` ranks highest in the given
+    /// precedence list (earlier = higher priority). Modes absent from the
+    /// list rank below every listed mode.
+    PreferMode(Vec<FunctionMode>),
+    /// Treat any unresolved real-vs-real conflict as a hard error so CI can
+    /// gate on it.
+    Error,
+}
+
+impl MergePolicy {
+    /// Default mode precedence: specification-bearing atoms win over proofs,
+    /// which win over executable code.
+    pub fn default_mode_precedence() -> Vec<FunctionMode> {
+        vec![FunctionMode::Spec, FunctionMode::Proof, FunctionMode::Exec]
+    }
+
+    /// Parse the value of the `--on-conflict` flag.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "first-wins" => Ok(MergePolicy::FirstWins),
+            "prefer-larger-span" => Ok(MergePolicy::PreferLargerSpan),
+            "prefer-mode" => Ok(MergePolicy::PreferMode(Self::default_mode_precedence())),
+            "error" => Ok(MergePolicy::Error),
+            other => Err(format!(
+                "unknown conflict policy '{}' (expected one of: \
+                 first-wins, prefer-larger-span, prefer-mode, error)",
+                other
+            )),
+        }
+    }
+}
+
+/// Number of source lines an atom's body spans.
+fn span_len(atom: &AtomWithLines) -> usize {
+    atom.code_text
+        .lines_end
+        .saturating_sub(atom.code_text.lines_start)
+}
+
+/// Rank of a mode within a precedence list (lower is higher priority);
+/// unlisted modes sort after every listed one.
+fn mode_rank(mode: &FunctionMode, precedence: &[FunctionMode]) -> usize {
+    precedence
+        .iter()
+        .position(|m| m == mode)
+        .unwrap_or(precedence.len())
 }
 
 /// Normalize all keys and dependency references in an atoms map.
@@ -47,21 +133,6 @@ fn normalize_atoms_map(
     (normalized, changed)
 }
 
-/// Load an atoms.json file into a BTreeMap, reconstructing code_name fields
-/// from the dictionary keys (since code_name is skip_serializing).
-fn load_atoms_file(path: &PathBuf) -> Result<BTreeMap<String, AtomWithLines>, String> {
-    let content = std::fs::read_to_string(path)
-        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
-    let mut atoms: BTreeMap<String, AtomWithLines> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
-
-    for (key, atom) in atoms.iter_mut() {
-        atom.code_name = key.clone();
-    }
-
-    Ok(atoms)
-}
-
 /// Merge result statistics.
 pub struct MergeStats {
     pub total_atoms: usize,
@@ -70,6 +141,8 @@ pub struct MergeStats {
     pub atoms_added: usize,
     pub keys_normalized: usize,
     pub conflicts: usize,
+    pub conflicts_resolved_by_span: usize,
+    pub conflicts_resolved_by_mode: usize,
 }
 
 /// Merge multiple atoms maps into one.
@@ -77,9 +150,21 @@ pub struct MergeStats {
 /// The first map is the base. For each subsequent map:
 /// - Stubs in the base are replaced by real atoms from the incoming map
 /// - New atoms (not in base) are added
-/// - Real-vs-real conflicts keep the base version (first wins)
+/// - Real-vs-real conflicts are resolved according to `policy`
 pub fn merge_atoms_maps(
     maps: Vec<BTreeMap<String, AtomWithLines>>,
+    policy: &MergePolicy,
+) -> (BTreeMap<String, AtomWithLines>, MergeStats) {
+    let mut diagnostics = Vec::new();
+    merge_atoms_maps_with_diagnostics(maps, policy, &mut diagnostics)
+}
+
+/// Like [`merge_atoms_maps`], but also collects structured diagnostics for CI
+/// consumption (real-vs-real conflicts and remaining stubs).
+fn merge_atoms_maps_with_diagnostics(
+    maps: Vec<BTreeMap<String, AtomWithLines>>,
+    policy: &MergePolicy,
+    diagnostics: &mut Vec<MergeDiagnostic>,
 ) -> (BTreeMap<String, AtomWithLines>, MergeStats) {
     let mut stats = MergeStats {
         total_atoms: 0,
@@ -88,6 +173,8 @@ pub fn merge_atoms_maps(
         atoms_added: 0,
         keys_normalized: 0,
         conflicts: 0,
+        conflicts_resolved_by_span: 0,
+        conflicts_resolved_by_mode: 0,
     };
 
     let mut maps_iter = maps.into_iter();
@@ -107,10 +194,41 @@ pub fn merge_atoms_maps(
                 }
                 Some(existing) if !is_stub(existing) && !is_stub(&incoming_atom) => {
                     stats.conflicts += 1;
-                    eprintln!(
-                        "  Warning: conflict for '{}' (keeping base version from {})",
-                        key, existing.code_path
-                    );
+                    diagnostics.push(MergeDiagnostic {
+                        severity: Severity::Warning,
+                        code_name: key.clone(),
+                        code_paths: vec![
+                            existing.code_path.clone(),
+                            incoming_atom.code_path.clone(),
+                        ],
+                        lines_start: vec![
+                            existing.code_text.lines_start,
+                            incoming_atom.code_text.lines_start,
+                        ],
+                        message: format!("real-vs-real conflict for '{}'", key),
+                    });
+                    match policy {
+                        MergePolicy::FirstWins | MergePolicy::Error => {
+                            eprintln!(
+                                "  Warning: conflict for '{}' (keeping base version from {})",
+                                key, existing.code_path
+                            );
+                        }
+                        MergePolicy::PreferLargerSpan => {
+                            if span_len(&incoming_atom) > span_len(existing) {
+                                base.insert(key, incoming_atom);
+                                stats.conflicts_resolved_by_span += 1;
+                            }
+                        }
+                        MergePolicy::PreferMode(precedence) => {
+                            if mode_rank(&incoming_atom.mode, precedence)
+                                < mode_rank(&existing.mode, precedence)
+                            {
+                                base.insert(key, incoming_atom);
+                                stats.conflicts_resolved_by_mode += 1;
+                            }
+                        }
+                    }
                 }
                 Some(_) => {
                     // Both stubs or incoming is stub -- keep base
@@ -126,11 +244,29 @@ pub fn merge_atoms_maps(
     stats.stubs_remaining = base.values().filter(|a| is_stub(a)).count();
     stats.total_atoms = base.len();
 
+    // Report every remaining stub. Reachability from real entry points is a
+    // call-graph property computed by the `analyze` subcommand, not here, so we
+    // deliberately do not claim these are "reachable" stubs.
+    for (key, atom) in base.iter().filter(|(_, a)| is_stub(a)) {
+        diagnostics.push(MergeDiagnostic {
+            severity: Severity::Info,
+            code_name: key.clone(),
+            code_paths: Vec::new(),
+            lines_start: Vec::new(),
+            message: format!("unresolved stub remains for '{}'", key),
+        });
+    }
+
     (base, stats)
 }
 
 /// Execute the merge-atoms command.
-pub fn cmd_merge_atoms(inputs: Vec<PathBuf>, output: PathBuf) {
+pub fn cmd_merge_atoms(
+    inputs: Vec<PathBuf>,
+    output: PathBuf,
+    policy: MergePolicy,
+    report: Option<PathBuf>,
+) {
     println!("═══════════════════════════════════════════════════════════");
     println!("  Probe Verus - Merge Atoms: Combine Indexed Projects");
     println!("═══════════════════════════════════════════════════════════");
@@ -158,10 +294,34 @@ pub fn cmd_merge_atoms(inputs: Vec<PathBuf>, output: PathBuf) {
     println!();
 
     println!("Merging {} files...", inputs.len());
-    let (merged, stats) = merge_atoms_maps(maps);
+    let mut diagnostics = Vec::new();
+    let (merged, stats) = merge_atoms_maps_with_diagnostics(maps, &policy, &mut diagnostics);
+
+    if let Some(report_path) = &report {
+        let json = serde_json::to_string_pretty(&diagnostics)
+            .expect("Failed to serialize diagnostics");
+        if let Err(e) = std::fs::write(report_path, &json) {
+            eprintln!("Error: failed to write report {}: {}", report_path.display(), e);
+            std::process::exit(1);
+        }
+    }
 
-    let json = serde_json::to_string_pretty(&merged).expect("Failed to serialize JSON");
-    std::fs::write(&output, &json).expect("Failed to write output file");
+    // Under the `Error` policy, any remaining real-vs-real conflict gates CI.
+    let unresolved_conflicts = stats.conflicts
+        - stats.conflicts_resolved_by_span
+        - stats.conflicts_resolved_by_mode;
+    if policy == MergePolicy::Error && unresolved_conflicts > 0 {
+        eprintln!(
+            "Error: {} unresolved real-vs-real conflict(s) under --on-conflict error",
+            unresolved_conflicts
+        );
+        std::process::exit(1);
+    }
+
+    if let Err(e) = write_atoms_file(&output, &merged) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
 
     println!();
     println!("═══════════════════════════════════════════════════════════");
@@ -177,7 +337,19 @@ pub fn cmd_merge_atoms(inputs: Vec<PathBuf>, output: PathBuf) {
         println!("  Keys normalized:  {}", stats.keys_normalized);
     }
     if stats.conflicts > 0 {
-        println!("  Conflicts (kept base): {}", stats.conflicts);
+        println!("  Conflicts:        {}", stats.conflicts);
+        if stats.conflicts_resolved_by_span > 0 {
+            println!(
+                "    resolved by span: {}",
+                stats.conflicts_resolved_by_span
+            );
+        }
+        if stats.conflicts_resolved_by_mode > 0 {
+            println!(
+                "    resolved by mode: {}",
+                stats.conflicts_resolved_by_mode
+            );
+        }
     }
     println!();
 }
@@ -239,7 +411,7 @@ mod tests {
             make_real_atom("helper", "probe:crate-b/1.0/mod/helper()", "src/lib.rs"),
         );
 
-        let (merged, stats) = merge_atoms_maps(vec![base, incoming]);
+        let (merged, stats) = merge_atoms_maps(vec![base, incoming], &MergePolicy::FirstWins);
 
         assert_eq!(stats.stubs_replaced, 1);
         assert_eq!(stats.stubs_remaining, 0);
@@ -262,7 +434,7 @@ mod tests {
             make_real_atom("bar", "probe:crate-b/1.0/mod/bar()", "src/bar.rs"),
         );
 
-        let (merged, stats) = merge_atoms_maps(vec![base, incoming]);
+        let (merged, stats) = merge_atoms_maps(vec![base, incoming], &MergePolicy::FirstWins);
 
         assert_eq!(stats.atoms_added, 1);
         assert_eq!(merged.len(), 2);
@@ -285,7 +457,7 @@ mod tests {
             make_real_atom("helper", "probe:crate-b/1.0/mod/helper()", "src/lib.rs"),
         );
 
-        let (merged, stats) = merge_atoms_maps(vec![base, incoming]);
+        let (merged, stats) = merge_atoms_maps(vec![base, incoming], &MergePolicy::FirstWins);
 
         assert_eq!(stats.keys_normalized, 1);
         assert_eq!(stats.stubs_replaced, 1);
@@ -304,7 +476,7 @@ mod tests {
             .insert("probe:crate-b/1.0/mod/helper().".to_string());
         base.insert("probe:crate-a/1.0/mod/caller()".to_string(), caller);
 
-        let (merged, _stats) = merge_atoms_maps(vec![base]);
+        let (merged, _stats) = merge_atoms_maps(vec![base], &MergePolicy::FirstWins);
 
         let caller = merged.get("probe:crate-a/1.0/mod/caller()").unwrap();
         assert!(caller
@@ -329,7 +501,7 @@ mod tests {
 
         let incoming = BTreeMap::new(); // empty
 
-        let (merged, stats) = merge_atoms_maps(vec![base, incoming]);
+        let (merged, stats) = merge_atoms_maps(vec![base, incoming], &MergePolicy::FirstWins);
 
         assert_eq!(stats.stubs_remaining, 1);
         assert_eq!(merged.len(), 2);
@@ -365,7 +537,7 @@ mod tests {
             make_real_atom("util", "probe:c/1.0/util()", "c/src/lib.rs"),
         );
 
-        let (merged, stats) = merge_atoms_maps(vec![map_a, map_b, map_c]);
+        let (merged, stats) = merge_atoms_maps(vec![map_a, map_b, map_c], &MergePolicy::FirstWins);
 
         assert_eq!(stats.stubs_replaced, 2);
         assert_eq!(stats.stubs_remaining, 0);
@@ -388,4 +560,75 @@ mod tests {
         let real = make_real_atom("f", "probe:c/1.0/f()", "src/lib.rs");
         assert!(!is_stub(&real));
     }
+
+    #[test]
+    fn test_conflict_first_wins_keeps_base() {
+        let mut base = BTreeMap::new();
+        base.insert(
+            "probe:a/1.0/f()".to_string(),
+            make_real_atom("f", "probe:a/1.0/f()", "base.rs"),
+        );
+        let mut incoming = BTreeMap::new();
+        incoming.insert(
+            "probe:a/1.0/f()".to_string(),
+            make_real_atom("f", "probe:a/1.0/f()", "incoming.rs"),
+        );
+
+        let (merged, stats) = merge_atoms_maps(vec![base, incoming], &MergePolicy::FirstWins);
+
+        assert_eq!(stats.conflicts, 1);
+        assert_eq!(merged.get("probe:a/1.0/f()").unwrap().code_path, "base.rs");
+    }
+
+    #[test]
+    fn test_conflict_prefer_larger_span() {
+        let mut base = BTreeMap::new();
+        base.insert(
+            "probe:a/1.0/f()".to_string(),
+            make_real_atom("f", "probe:a/1.0/f()", "base.rs"), // span 10..20 = 10
+        );
+        let mut incoming = BTreeMap::new();
+        let mut big = make_real_atom("f", "probe:a/1.0/f()", "incoming.rs");
+        big.code_text.lines_start = 10;
+        big.code_text.lines_end = 60; // span 50
+        incoming.insert("probe:a/1.0/f()".to_string(), big);
+
+        let (merged, stats) =
+            merge_atoms_maps(vec![base, incoming], &MergePolicy::PreferLargerSpan);
+
+        assert_eq!(stats.conflicts_resolved_by_span, 1);
+        assert_eq!(
+            merged.get("probe:a/1.0/f()").unwrap().code_path,
+            "incoming.rs"
+        );
+    }
+
+    #[test]
+    fn test_conflict_prefer_mode() {
+        let mut base = BTreeMap::new();
+        base.insert(
+            "probe:a/1.0/f()".to_string(),
+            make_real_atom("f", "probe:a/1.0/f()", "base.rs"), // Exec
+        );
+        let mut incoming = BTreeMap::new();
+        let mut spec = make_real_atom("f", "probe:a/1.0/f()", "incoming.rs");
+        spec.mode = FunctionMode::Spec;
+        incoming.insert("probe:a/1.0/f()".to_string(), spec);
+
+        let policy = MergePolicy::PreferMode(MergePolicy::default_mode_precedence());
+        let (merged, stats) = merge_atoms_maps(vec![base, incoming], &policy);
+
+        assert_eq!(stats.conflicts_resolved_by_mode, 1);
+        assert_eq!(
+            merged.get("probe:a/1.0/f()").unwrap().code_path,
+            "incoming.rs"
+        );
+    }
+
+    #[test]
+    fn test_merge_policy_parse() {
+        assert_eq!(MergePolicy::parse("first-wins"), Ok(MergePolicy::FirstWins));
+        assert_eq!(MergePolicy::parse("error"), Ok(MergePolicy::Error));
+        assert!(MergePolicy::parse("bogus").is_err());
+    }
 }
@@ -0,0 +1,82 @@
+//! Merge-metrics command - Combine per-run verification metrics into a
+//! cross-run time-series.
+//!
+//! Mirrors the `merge-atoms` command: each input is one run's `metrics.json`
+//! (or an already-combined time-series), and combination appends later runs
+//! rather than overwriting, producing one document from which trends can be
+//! computed.
+
+use probe_verus::metrics::{MetricsTimeSeries, RunMetrics};
+use std::path::PathBuf;
+
+/// Execute the merge-metrics command.
+pub fn cmd_merge_metrics(inputs: Vec<PathBuf>, output: PathBuf) {
+    println!("═══════════════════════════════════════════════════════════");
+    println!("  Probe Verus - Merge Metrics: Combine Verification Runs");
+    println!("═══════════════════════════════════════════════════════════");
+    println!();
+
+    if inputs.is_empty() {
+        eprintln!("Error: merge-metrics requires at least 1 input file");
+        std::process::exit(1);
+    }
+
+    let mut series = MetricsTimeSeries::default();
+    for path in &inputs {
+        println!("  Loading {}...", path.display());
+        match load_input(path) {
+            Ok(Input::Run(run)) => {
+                let atoms = run.atoms.len();
+                series.append_run(run);
+                println!("    run: {} atom(s)", atoms);
+            }
+            Ok(Input::Series(existing)) => {
+                let atoms = existing.atoms.len();
+                series.extend(existing);
+                println!("    time-series: {} atom(s)", atoms);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    println!();
+
+    let json = serde_json::to_string_pretty(&series).expect("Failed to serialize JSON");
+    std::fs::write(&output, &json).expect("Failed to write output file");
+
+    let total_samples: usize = series.atoms.values().map(|s| s.len()).sum();
+    println!("═══════════════════════════════════════════════════════════");
+    println!("  Merge complete");
+    println!("═══════════════════════════════════════════════════════════");
+    println!();
+    println!("Output: {}", output.display());
+    println!("  Atoms tracked:   {}", series.atoms.len());
+    println!("  Total samples:   {}", total_samples);
+    println!();
+}
+
+/// An input file is either a single run or an already-combined time-series.
+enum Input {
+    Run(RunMetrics),
+    Series(MetricsTimeSeries),
+}
+
+/// Load an input, auto-detecting a single run vs. a combined time-series.
+///
+/// A combined document has a top-level `atoms` map of arrays, while a single
+/// run has an `atoms` map of objects; the two shapes are mutually exclusive,
+/// so we try the time-series first and fall back to a single run.
+fn load_input(path: &PathBuf) -> Result<Input, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    if let Ok(series) = serde_json::from_str::<MetricsTimeSeries>(&content) {
+        return Ok(Input::Series(series));
+    }
+
+    serde_json::from_str::<RunMetrics>(&content)
+        .map(Input::Run)
+        .map_err(|e| format!("Failed to parse {} as metrics: {}", path.display(), e))
+}
@@ -6,15 +6,21 @@
 //! - `functions`: List all functions in a project
 //! - `specify`: Extract function specifications to JSON
 //! - `run`: Run both atomize and verify (for CI/Docker)
+//! - `analyze`: Inspect call-graph structure of a merged atoms.json
 
+mod analyze;
+mod atoms;
 mod atomize;
 mod functions;
+mod merge_metrics;
 mod run;
 mod specify;
 mod verify;
 
+pub use analyze::cmd_analyze;
 pub use atomize::cmd_atomize;
 pub use functions::cmd_functions;
+pub use merge_metrics::cmd_merge_metrics;
 pub use run::cmd_run;
 pub use specify::cmd_specify;
 pub use verify::cmd_verify;
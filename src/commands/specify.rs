@@ -1,6 +1,5 @@
 //! Specify command - Extract function specifications to JSON.
 
-use probe_verus::constants::LINE_TOLERANCE;
 use probe_verus::path_utils::{extract_src_suffix, paths_match_by_suffix};
 use probe_verus::taxonomy;
 use probe_verus::verus_parser::{self, FunctionInfo, ParsedOutput};
@@ -205,19 +204,75 @@ fn match_functions_to_atoms(
     (output_map, matched_count, unmatched_count)
 }
 
+/// A structured SCIP-style atom key.
+///
+/// The atom keys are already structured monikers
+/// (`probe:crate/<version>/edwards/EdwardsPoint#Add#add()`), so we parse them
+/// directly rather than guessing by line number. After stripping the
+/// `probe:<package>/<version>/` scheme prefix, the descriptor tail is walked:
+/// `/`-terminated segments are module-path components, a descriptor terminated
+/// by `#` is an enclosing type or trait (two consecutive `#` segments for
+/// trait-impl methods, i.e. `Type#Trait#`), and the final descriptor
+/// terminated by `()` is the method/function name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AtomSymbol {
+    module_path: Vec<String>,
+    enclosing_type: Option<String>,
+    trait_name: Option<String>,
+    method: String,
+}
+
+impl AtomSymbol {
+    /// Parse an atom key into its structured components, or `None` if it does
+    /// not look like a function/method moniker.
+    fn parse(key: &str) -> Option<Self> {
+        // Strip `probe:` scheme, then drop the `<package>/<version>/` prefix.
+        let rest = key.strip_prefix("probe:").unwrap_or(key);
+        let mut parts = rest.splitn(3, '/');
+        let _package = parts.next()?;
+        let _version = parts.next()?;
+        let tail = parts.next()?;
+
+        // The module path is every `/`-separated segment before the final
+        // descriptor (which carries the type/trait/method).
+        let mut segments: Vec<&str> = tail.split('/').collect();
+        let descriptor = segments.pop()?;
+        let module_path: Vec<String> = segments.iter().map(|s| s.to_string()).collect();
+
+        // The final descriptor is `Type#Trait#method()` with zero, one, or two
+        // leading `#`-terminated type/trait descriptors.
+        let hash_parts: Vec<&str> = descriptor.split('#').collect();
+        let method = hash_parts.last()?.strip_suffix("()")?.to_string();
+        if method.is_empty() {
+            return None;
+        }
+        let enclosing_type = (hash_parts.len() >= 2).then(|| hash_parts[0].to_string());
+        let trait_name = (hash_parts.len() >= 3).then(|| hash_parts[1].to_string());
+
+        Some(AtomSymbol {
+            module_path,
+            enclosing_type,
+            trait_name,
+            method,
+        })
+    }
+}
+
 /// Find the best matching atom for a function.
 ///
-/// Matching strategy:
-/// 1. Path must match (by suffix comparison)
-/// 2. Name must match: either exact equality or the atom's display name
-///    ends with `::func.name` (handles impl methods where SCIP enriches
-///    display names to `Type::method` while verus_syn yields bare identifiers)
-/// 3. SCIP line must fall within the function's span [start_line, end_line]
-///    OR be within LINE_TOLERANCE of fn_line
+/// Matching strategy (structural, not line-based):
+/// 1. Path must match (by suffix comparison).
+/// 2. The parsed symbol's `method` must equal `func.name`.
+/// 3. When both the symbol's `enclosing_type` and `func.impl_type` are known
+///    they must be equal (disambiguates same-named methods on different
+///    types).
+/// 4. Module paths, when both are known, must be compatible (the symbol's
+///    module path is a suffix of the function's).
 ///
-/// Uses `fn_line` (the `fn` keyword line) for distance calculation since it
-/// closely matches SCIP's definition line, unlike `spec_text.lines_start`
-/// which includes preceding doc comments and attributes.
+/// The line number is used only as a final tiebreaker among structurally
+/// identical candidates, via `fn_line` (the `fn` keyword line) which closely
+/// matches SCIP's definition line. This makes matching exact for impl methods
+/// and removes false negatives when code simply moved.
 fn find_matching_atom(func: &FunctionInfo, atoms: &BTreeMap<String, AtomEntry>) -> Option<String> {
     let func_path = func.file.as_deref().unwrap_or("");
     let func_suffix = extract_src_suffix(func_path);
@@ -230,37 +285,39 @@ fn find_matching_atom(func: &FunctionInfo, atoms: &BTreeMap<String, AtomEntry>)
 
         let path_matches =
             paths_match_by_suffix(func_path, &atom.code_path) || func_suffix == atom_suffix;
+        if !path_matches {
+            continue;
+        }
 
-        let name_matches = func.name == atom.display_name
-            || atom.display_name.ends_with(&format!("::{}", func.name));
-
-        if path_matches && name_matches {
-            let atom_line = atom.code_text.lines_start;
-
-            // Check if SCIP line falls within the function span [start_line, end_line]
-            // This handles doc comments being included in verus_syn's span
-            let within_span =
-                atom_line >= func.spec_text.lines_start && atom_line <= func.spec_text.lines_end;
+        let Some(symbol) = AtomSymbol::parse(code_name) else {
+            continue;
+        };
 
-            let line_diff = (func.fn_line as isize - atom_line as isize).unsigned_abs();
-            let within_tolerance = line_diff <= LINE_TOLERANCE;
+        // Name must match the parsed method descriptor.
+        if func.name != symbol.method {
+            continue;
+        }
 
-            if within_span || within_tolerance {
-                // Prefer matches closer to fn_line
-                let effective_diff = if within_span && !within_tolerance {
-                    (func.fn_line as isize - atom_line as isize).unsigned_abs()
-                } else {
-                    line_diff
-                };
+        // Enclosing type must match when both sides know it.
+        if let (Some(enclosing), Some(impl_type)) = (&symbol.enclosing_type, &func.impl_type) {
+            if enclosing != impl_type {
+                continue;
+            }
+        }
 
-                if effective_diff < best_line_diff {
-                    best_match = Some(code_name);
-                    best_line_diff = effective_diff;
+        // Module paths must be compatible when both are known.
+        if !module_paths_compatible(&symbol.module_path, func.module_path.as_deref()) {
+            continue;
+        }
 
-                    if effective_diff == 0 {
-                        break;
-                    }
-                }
+        // Structurally compatible: use the line number only as a tiebreaker.
+        let atom_line = atom.code_text.lines_start;
+        let line_diff = (func.fn_line as isize - atom_line as isize).unsigned_abs();
+        if line_diff < best_line_diff {
+            best_match = Some(code_name);
+            best_line_diff = line_diff;
+            if line_diff == 0 {
+                break;
             }
         }
     }
@@ -268,6 +325,32 @@ fn find_matching_atom(func: &FunctionInfo, atoms: &BTreeMap<String, AtomEntry>)
     best_match.map(|s| s.to_string())
 }
 
+/// Whether a symbol's module path is compatible with a function's module path.
+///
+/// Compatible means the symbol's components are a suffix of the function's
+/// `::`-separated module path. When either side is unknown, we consider them
+/// compatible (there is nothing to contradict).
+fn module_paths_compatible(symbol_modules: &[String], func_module: Option<&str>) -> bool {
+    if symbol_modules.is_empty() {
+        return true;
+    }
+    let Some(func_module) = func_module else {
+        return true;
+    };
+    let func_segments: Vec<&str> = func_module.split("::").filter(|s| !s.is_empty()).collect();
+    if func_segments.is_empty() {
+        return true;
+    }
+    // The symbol's module components should appear as a suffix of the function
+    // module (SCIP may report a shorter, crate-relative path).
+    symbol_modules.len() <= func_segments.len()
+        && symbol_modules
+            .iter()
+            .rev()
+            .zip(func_segments.iter().rev())
+            .all(|(a, b)| a == b)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -412,7 +495,10 @@ mod tests {
     }
 
     #[test]
-    fn test_no_match_when_line_too_far() {
+    fn test_structural_match_when_code_moved() {
+        // The `fn` keyword moved far from the SCIP line (e.g. doc comments
+        // added), but the symbol is structurally unambiguous, so it still
+        // matches -- line number is only a tiebreaker now.
         let func = make_func("add", "src/edwards.rs", 500, 498, 510);
         let mut atoms = BTreeMap::new();
         atoms.insert(
@@ -420,7 +506,28 @@ mod tests {
             make_atom("EdwardsPoint::add", "src/edwards.rs", 100),
         );
         let result = find_matching_atom(&func, &atoms);
-        assert_eq!(result, None);
+        assert_eq!(
+            result,
+            Some("probe:crate/1.0/edwards/EdwardsPoint#Add#add()".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_atom_symbol_trait_impl() {
+        let sym = AtomSymbol::parse("probe:crate/1.0/edwards/EdwardsPoint#Add#add()").unwrap();
+        assert_eq!(sym.module_path, vec!["edwards".to_string()]);
+        assert_eq!(sym.enclosing_type, Some("EdwardsPoint".to_string()));
+        assert_eq!(sym.trait_name, Some("Add".to_string()));
+        assert_eq!(sym.method, "add");
+    }
+
+    #[test]
+    fn test_parse_atom_symbol_free_function() {
+        let sym = AtomSymbol::parse("probe:crate/1.0/edwards/decompress()").unwrap();
+        assert_eq!(sym.module_path, vec!["edwards".to_string()]);
+        assert_eq!(sym.enclosing_type, None);
+        assert_eq!(sym.trait_name, None);
+        assert_eq!(sym.method, "decompress");
     }
 
     #[test]
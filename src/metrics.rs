@@ -0,0 +1,160 @@
+//! Verification metrics time-series subsystem.
+//!
+//! The `verify`/`run` commands produce per-atom verification results that are
+//! discarded after each invocation. This module captures those results into a
+//! structured `metrics.json` and combines many such files into a single
+//! time-series keyed by atom and run, so a project can compute trends over its
+//! history: functions that newly regress, verification-time deltas, and
+//! stub-resolution progress across commits.
+//!
+//! The combination semantics mirror the atoms merge (see the CLI's
+//! `merge-atoms` command): combining is a deep-merge where later
+//! runs *append* samples rather than overwriting earlier ones.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Per-atom verification result captured during a single run.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AtomMetrics {
+    /// Whether the atom verified successfully.
+    pub verified: bool,
+    /// Whether verification failed (distinct from "not attempted").
+    pub failed: bool,
+    /// Wall-clock time spent verifying this atom, in milliseconds.
+    #[serde(rename = "wall-clock-ms")]
+    pub wall_clock_ms: u64,
+    /// Number of verification errors reported for this atom.
+    #[serde(rename = "error-count")]
+    pub error_count: usize,
+}
+
+/// A single verification run over a project.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RunMetrics {
+    /// Unix timestamp (seconds) when the run was captured.
+    pub timestamp: u64,
+    /// Commit / source tag identifying the project state for this run.
+    #[serde(rename = "source-tag")]
+    pub source_tag: String,
+    /// Per-atom results, keyed by `code_name`.
+    pub atoms: BTreeMap<String, AtomMetrics>,
+}
+
+impl RunMetrics {
+    /// Write this run's metrics to `path` as pretty JSON.
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize metrics: {}", e))?;
+        std::fs::write(path, &json)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+/// A single sample in the combined time-series: one atom's result in one run.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MetricSample {
+    pub timestamp: u64,
+    #[serde(rename = "source-tag")]
+    pub source_tag: String,
+    #[serde(flatten)]
+    pub metrics: AtomMetrics,
+}
+
+/// Combined cross-run time-series, keyed by atom then ordered by run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MetricsTimeSeries {
+    /// For each atom `code_name`, the samples accumulated across runs in the
+    /// order they were combined.
+    pub atoms: BTreeMap<String, Vec<MetricSample>>,
+}
+
+impl MetricsTimeSeries {
+    /// Append a single run's results to the time-series (later runs append
+    /// rather than overwrite).
+    pub fn append_run(&mut self, run: RunMetrics) {
+        for (code_name, metrics) in run.atoms {
+            self.atoms.entry(code_name).or_default().push(MetricSample {
+                timestamp: run.timestamp,
+                source_tag: run.source_tag.clone(),
+                metrics,
+            });
+        }
+    }
+
+    /// Combine many runs into one time-series, preserving input order.
+    pub fn combine(runs: impl IntoIterator<Item = RunMetrics>) -> Self {
+        let mut series = MetricsTimeSeries::default();
+        for run in runs {
+            series.append_run(run);
+        }
+        series
+    }
+
+    /// Combine with an existing time-series document (so `merge-metrics` can be
+    /// re-run incrementally), appending all samples from `other`.
+    pub fn extend(&mut self, other: MetricsTimeSeries) {
+        for (code_name, samples) in other.atoms {
+            self.atoms.entry(code_name).or_default().extend(samples);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(tag: &str, ts: u64, entries: &[(&str, bool)]) -> RunMetrics {
+        RunMetrics {
+            timestamp: ts,
+            source_tag: tag.to_string(),
+            atoms: entries
+                .iter()
+                .map(|(name, verified)| {
+                    (
+                        name.to_string(),
+                        AtomMetrics {
+                            verified: *verified,
+                            failed: !*verified,
+                            wall_clock_ms: 100,
+                            error_count: if *verified { 0 } else { 1 },
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_combine_appends_per_run() {
+        let r1 = run("c1", 1, &[("f", true)]);
+        let r2 = run("c2", 2, &[("f", false)]);
+        let series = MetricsTimeSeries::combine(vec![r1, r2]);
+
+        let samples = &series.atoms["f"];
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].source_tag, "c1");
+        assert!(samples[0].metrics.verified);
+        assert_eq!(samples[1].source_tag, "c2");
+        assert!(samples[1].metrics.failed);
+    }
+
+    #[test]
+    fn test_new_atom_in_later_run() {
+        let r1 = run("c1", 1, &[("f", true)]);
+        let r2 = run("c2", 2, &[("f", true), ("g", true)]);
+        let series = MetricsTimeSeries::combine(vec![r1, r2]);
+
+        assert_eq!(series.atoms["f"].len(), 2);
+        assert_eq!(series.atoms["g"].len(), 1);
+    }
+
+    #[test]
+    fn test_extend_merges_documents() {
+        let mut a = MetricsTimeSeries::combine(vec![run("c1", 1, &[("f", true)])]);
+        let b = MetricsTimeSeries::combine(vec![run("c2", 2, &[("f", false)])]);
+        a.extend(b);
+        assert_eq!(a.atoms["f"].len(), 2);
+    }
+}
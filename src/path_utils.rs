@@ -4,6 +4,7 @@
 //! matching strategies. This is essential because different tools (verus-analyzer,
 //! verus_syn, Verus compiler) may report paths in different formats.
 
+use std::collections::BTreeMap;
 use std::path::Path;
 
 /// Extract the "src/..." suffix from a path for normalized matching.
@@ -117,20 +118,136 @@ where
     }
 }
 
+/// Identifier assigned to a registered pattern, in registration order.
+pub type PatternId = usize;
+
+/// A node in the pattern-matching segment trie.
+///
+/// Matching prefers static children, then the dynamic `{name}` child, then the
+/// tail child at each level (the same priority as [`PathMatchScore`]:
+/// exact/static > suffix/dynamic > filename/tail).
+#[derive(Debug, Clone, Default)]
+struct TrieNode {
+    /// Literal-segment children keyed by the segment text.
+    static_children: BTreeMap<String, TrieNode>,
+    /// At most one `{name}` child; stores the capture name.
+    dynamic_child: Option<(String, Box<TrieNode>)>,
+    /// Optional tail `*name` terminal; stores the capture name and pattern id.
+    tail_child: Option<(String, PatternId)>,
+    /// Pattern id if a registered pattern terminates exactly at this node.
+    pattern: Option<PatternId>,
+}
+
+/// One parsed pattern segment.
+enum Segment {
+    Static(String),
+    Dynamic(String),
+    Tail(String),
+}
+
+fn parse_segment(seg: &str) -> Segment {
+    if let Some(name) = seg.strip_prefix('*') {
+        Segment::Tail(name.to_string())
+    } else if seg.len() >= 2 && seg.starts_with('{') && seg.ends_with('}') {
+        Segment::Dynamic(seg[1..seg.len() - 1].to_string())
+    } else {
+        Segment::Static(seg.to_string())
+    }
+}
+
 /// A helper for efficiently looking up paths from a known set.
 ///
 /// This struct provides O(1) amortized lookup for path matching,
-/// with fuzzy matching support (exact > suffix > filename-only).
+/// with fuzzy matching support (exact > suffix > filename-only), plus a
+/// route-style pattern matcher for paths whose intermediate directory layout
+/// differs between tools.
 #[derive(Debug, Clone)]
 pub struct PathMatcher {
     /// All known paths
     known_paths: Vec<String>,
+    /// Root of the registered-pattern trie.
+    pattern_root: TrieNode,
+    /// Number of patterns registered so far (also the next `PatternId`).
+    pattern_count: usize,
 }
 
 impl PathMatcher {
     /// Create a new PathMatcher with the given known paths.
     pub fn new(paths: Vec<String>) -> Self {
-        Self { known_paths: paths }
+        Self {
+            known_paths: paths,
+            pattern_root: TrieNode::default(),
+            pattern_count: 0,
+        }
+    }
+
+    /// Register a route-style pattern and return its [`PatternId`].
+    ///
+    /// Patterns are `/`-separated and may contain static segments, named
+    /// captures (`{crate}`), and a trailing tail wildcard (`*rest`) that
+    /// consumes all remaining query segments into one capture.
+    ///
+    /// # Errors
+    /// A tail segment may not be followed by any further segment; registering
+    /// such a pattern fails.
+    pub fn register_pattern(&mut self, pattern: &str) -> Result<PatternId, String> {
+        let segments: Vec<Segment> = pattern
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(parse_segment)
+            .collect();
+
+        // Validate: a tail segment must be last.
+        if let Some(pos) = segments
+            .iter()
+            .position(|s| matches!(s, Segment::Tail(_)))
+        {
+            if pos != segments.len() - 1 {
+                return Err(format!(
+                    "pattern '{}' has segments after a tail wildcard",
+                    pattern
+                ));
+            }
+        }
+
+        let id = self.pattern_count;
+        let mut node = &mut self.pattern_root;
+        for (i, seg) in segments.iter().enumerate() {
+            match seg {
+                Segment::Static(lit) => {
+                    node = node.static_children.entry(lit.clone()).or_default();
+                }
+                Segment::Dynamic(name) => {
+                    if node.dynamic_child.is_none() {
+                        node.dynamic_child = Some((name.clone(), Box::new(TrieNode::default())));
+                    }
+                    node = &mut node.dynamic_child.as_mut().unwrap().1;
+                }
+                Segment::Tail(name) => {
+                    // Tail terminates the pattern (validated above to be last).
+                    node.tail_child = Some((name.clone(), id));
+                    self.pattern_count += 1;
+                    return Ok(id);
+                }
+            }
+            // The last non-tail segment terminates the pattern.
+            if i == segments.len() - 1 {
+                node.pattern = Some(id);
+            }
+        }
+
+        self.pattern_count += 1;
+        Ok(id)
+    }
+
+    /// Match a path against the registered patterns.
+    ///
+    /// Returns the matched pattern id and a map of captured segment values, or
+    /// `None` if no pattern consumes the full query (unless a tail matched).
+    pub fn match_pattern(&self, query: &str) -> Option<(PatternId, BTreeMap<String, String>)> {
+        let segments: Vec<&str> = query.split('/').filter(|s| !s.is_empty()).collect();
+        let mut captures = BTreeMap::new();
+        match_node(&self.pattern_root, &segments, &mut captures).map(|id| (id, captures))
     }
 
     /// Find the best matching known path for the given query.
@@ -167,6 +284,52 @@ impl PathMatcher {
     }
 }
 
+/// Recursively match query `segments` against `node`, preferring static over
+/// dynamic over tail and requiring full consumption of the query.
+fn match_node(
+    node: &TrieNode,
+    segments: &[&str],
+    captures: &mut BTreeMap<String, String>,
+) -> Option<PatternId> {
+    if segments.is_empty() {
+        if let Some(id) = node.pattern {
+            return Some(id);
+        }
+        if let Some((name, id)) = &node.tail_child {
+            captures.insert(name.clone(), String::new());
+            return Some(*id);
+        }
+        return None;
+    }
+
+    let first = segments[0];
+    let rest = &segments[1..];
+
+    // Static segments have highest priority.
+    if let Some(child) = node.static_children.get(first) {
+        if let Some(id) = match_node(child, rest, captures) {
+            return Some(id);
+        }
+    }
+
+    // Then the single dynamic `{name}` child.
+    if let Some((name, child)) = &node.dynamic_child {
+        captures.insert(name.clone(), first.to_string());
+        if let Some(id) = match_node(child, rest, captures) {
+            return Some(id);
+        }
+        captures.remove(name);
+    }
+
+    // Finally a tail, which consumes all remaining segments.
+    if let Some((name, id)) = &node.tail_child {
+        captures.insert(name.clone(), segments.join("/"));
+        return Some(*id);
+    }
+
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -238,4 +401,58 @@ mod tests {
         let result = matcher.find_best_match("constants_lemmas.rs");
         assert!(result.is_some());
     }
+
+    #[test]
+    fn test_register_pattern_static_and_named() {
+        let mut matcher = PathMatcher::new(vec![]);
+        let id = matcher
+            .register_pattern("target/{crate}/src/lib.rs")
+            .unwrap();
+
+        let (matched, caps) = matcher.match_pattern("target/field/src/lib.rs").unwrap();
+        assert_eq!(matched, id);
+        assert_eq!(caps.get("crate").map(String::as_str), Some("field"));
+    }
+
+    #[test]
+    fn test_tail_captures_remaining_segments() {
+        let mut matcher = PathMatcher::new(vec![]);
+        let id = matcher.register_pattern("src/{crate}/*rest").unwrap();
+
+        let (matched, caps) = matcher
+            .match_pattern("src/edwards/lemmas/field.rs")
+            .unwrap();
+        assert_eq!(matched, id);
+        assert_eq!(caps.get("crate").map(String::as_str), Some("edwards"));
+        assert_eq!(caps.get("rest").map(String::as_str), Some("lemmas/field.rs"));
+    }
+
+    #[test]
+    fn test_static_preferred_over_dynamic() {
+        let mut matcher = PathMatcher::new(vec![]);
+        let specific = matcher.register_pattern("src/lib.rs").unwrap();
+        let generic = matcher.register_pattern("src/{file}").unwrap();
+
+        let (matched, _) = matcher.match_pattern("src/lib.rs").unwrap();
+        assert_eq!(matched, specific);
+
+        let (matched, caps) = matcher.match_pattern("src/main.rs").unwrap();
+        assert_eq!(matched, generic);
+        assert_eq!(caps.get("file").map(String::as_str), Some("main.rs"));
+    }
+
+    #[test]
+    fn test_no_match_when_query_not_fully_consumed() {
+        let mut matcher = PathMatcher::new(vec![]);
+        matcher.register_pattern("src/lib.rs").unwrap();
+        assert!(matcher.match_pattern("src/lib.rs/extra").is_none());
+        assert!(matcher.match_pattern("other/lib.rs").is_none());
+    }
+
+    #[test]
+    fn test_tail_must_be_last_segment() {
+        let mut matcher = PathMatcher::new(vec![]);
+        let err = matcher.register_pattern("src/*rest/lib.rs").unwrap_err();
+        assert!(err.contains("after a tail wildcard"));
+    }
 }
@@ -5,21 +5,110 @@
 //! projects, so caching is important for developer experience.
 
 use crate::constants::{DATA_DIR, SCIP_INDEX_FILE, SCIP_INDEX_JSON_FILE};
-use std::path::PathBuf;
+use prost::Message;
+use scip::types::Index;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::mpsc;
+
+/// Filename of the source-freshness manifest stored in the data directory.
+const MANIFEST_FILE: &str = "index.manifest.json";
 
 /// Which language server to use for SCIP index generation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Analyzer {
+pub enum AnalyzerKind {
     VerusAnalyzer,
     RustAnalyzer,
 }
 
-impl Analyzer {
+impl AnalyzerKind {
+    /// The default command name looked up on `PATH`.
     pub fn command_name(&self) -> &'static str {
         match self {
-            Analyzer::VerusAnalyzer => "verus-analyzer",
-            Analyzer::RustAnalyzer => "rust-analyzer",
+            AnalyzerKind::VerusAnalyzer => "verus-analyzer",
+            AnalyzerKind::RustAnalyzer => "rust-analyzer",
+        }
+    }
+
+    /// Minimum `--version` triple that emits the SCIP schema this crate
+    /// decodes. The two analyzers version independently: verus-analyzer uses
+    /// its own `0.x` line, while standalone rust-analyzer still reports a
+    /// `0.3.xxxx-standalone` scheme, so they need separate floors.
+    pub fn min_version(&self) -> (u64, u64, u64) {
+        match self {
+            AnalyzerKind::VerusAnalyzer => (0, 4, 0),
+            AnalyzerKind::RustAnalyzer => (0, 3, 0),
+        }
+    }
+}
+
+impl std::fmt::Display for AnalyzerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.command_name())
+    }
+}
+
+/// A chosen analyzer plus an optional explicit path to its binary.
+///
+/// When `binary` is `None` the analyzer is resolved by walking `PATH` (honoring
+/// `PATHEXT`/`.exe` on Windows); when set, that path is invoked directly,
+/// letting callers point at a binary that isn't on `PATH`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analyzer {
+    kind: AnalyzerKind,
+    binary: Option<PathBuf>,
+}
+
+impl Analyzer {
+    /// The verus-analyzer, resolved from `PATH`.
+    pub fn verus_analyzer() -> Self {
+        Self::new(AnalyzerKind::VerusAnalyzer)
+    }
+
+    /// The rust-analyzer, resolved from `PATH`.
+    pub fn rust_analyzer() -> Self {
+        Self::new(AnalyzerKind::RustAnalyzer)
+    }
+
+    /// An analyzer of the given kind, resolved from `PATH`.
+    pub fn new(kind: AnalyzerKind) -> Self {
+        Self { kind, binary: None }
+    }
+
+    /// Override the binary path instead of resolving the command name on `PATH`.
+    pub fn with_binary(mut self, path: impl Into<PathBuf>) -> Self {
+        self.binary = Some(path.into());
+        self
+    }
+
+    /// The kind of analyzer this is.
+    pub fn kind(&self) -> AnalyzerKind {
+        self.kind
+    }
+
+    /// The command name used in diagnostics.
+    pub fn command_name(&self) -> &'static str {
+        self.kind.command_name()
+    }
+
+    /// The program to execute: the explicit override if set, else the command
+    /// name (resolved on `PATH` by the OS).
+    pub fn program(&self) -> PathBuf {
+        match &self.binary {
+            Some(path) => path.clone(),
+            None => PathBuf::from(self.kind.command_name()),
+        }
+    }
+
+    /// Whether the analyzer binary is available: the explicit path exists, or
+    /// the command name resolves on `PATH`.
+    pub fn is_available(&self) -> bool {
+        match &self.binary {
+            Some(path) => path.is_file(),
+            None => find_in_path(self.kind.command_name()).is_some(),
         }
     }
 }
@@ -35,6 +124,8 @@ impl std::fmt::Display for Analyzer {
 pub enum ScipError {
     /// Analyzer command not found in PATH
     AnalyzerNotFound(Analyzer),
+    /// Analyzer is too old to emit the SCIP schema this crate decodes
+    AnalyzerTooOld { found: String, required: String },
     /// scip CLI command not found in PATH
     ScipCliNotFound,
     /// Analyzer scip command failed
@@ -49,6 +140,10 @@ pub enum ScipError {
     MoveFileFailed(std::io::Error),
     /// Failed to write JSON file
     WriteJsonFailed(std::io::Error),
+    /// Failed to read the binary index.scip file
+    ReadIndexFailed(std::io::Error),
+    /// Failed to decode the index.scip protobuf
+    DecodeFailed(String),
 }
 
 impl std::fmt::Display for ScipError {
@@ -57,6 +152,13 @@ impl std::fmt::Display for ScipError {
             ScipError::AnalyzerNotFound(a) => {
                 write!(f, "{} not found in PATH", a)
             }
+            ScipError::AnalyzerTooOld { found, required } => {
+                write!(
+                    f,
+                    "analyzer too old to emit a decodable SCIP index: found {}, requires >= {}",
+                    found, required
+                )
+            }
             ScipError::ScipCliNotFound => {
                 write!(f, "scip not found in PATH")
             }
@@ -82,6 +184,12 @@ impl std::fmt::Display for ScipError {
             ScipError::WriteJsonFailed(e) => {
                 write!(f, "failed to write SCIP JSON: {}", e)
             }
+            ScipError::ReadIndexFailed(e) => {
+                write!(f, "failed to read index.scip: {}", e)
+            }
+            ScipError::DecodeFailed(msg) => {
+                write!(f, "failed to decode index.scip: {}", msg)
+            }
         }
     }
 }
@@ -96,6 +204,14 @@ impl std::error::Error for ScipError {}
 pub struct ScipCache {
     project_path: PathBuf,
     analyzer: Analyzer,
+    /// Explicit crate roots for a synthesized `rust-project.json`. When set,
+    /// the descriptor is always generated; when `None`, it is auto-generated
+    /// only for projects without a `Cargo.toml`.
+    project_json_roots: Option<Vec<PathBuf>>,
+    /// Override for the data directory. When `None`, defaults to
+    /// `<project>/data`. Used by [`ScipWorkspace`] to store each member's
+    /// index under `data/<member>/`.
+    data_dir_override: Option<PathBuf>,
 }
 
 impl ScipCache {
@@ -103,7 +219,9 @@ impl ScipCache {
     pub fn new(project_path: impl Into<PathBuf>) -> Self {
         Self {
             project_path: project_path.into(),
-            analyzer: Analyzer::VerusAnalyzer,
+            analyzer: Analyzer::verus_analyzer(),
+            project_json_roots: None,
+            data_dir_override: None,
         }
     }
 
@@ -112,12 +230,34 @@ impl ScipCache {
         Self {
             project_path: project_path.into(),
             analyzer,
+            project_json_roots: None,
+            data_dir_override: None,
         }
     }
 
+    /// Override the data directory used to cache this crate's index. Used by
+    /// [`ScipWorkspace`] so each member writes into `data/<member>/`.
+    pub fn with_data_dir(mut self, data_dir: impl Into<PathBuf>) -> Self {
+        self.data_dir_override = Some(data_dir.into());
+        self
+    }
+
+    /// Configure explicit crate roots for a synthesized `rust-project.json`.
+    ///
+    /// Use this for Verus projects laid out as loose `.rs` files or custom
+    /// build setups that neither `cargo` nor `verus-analyzer scip .` can
+    /// discover. Each root becomes a crate in the generated descriptor.
+    pub fn with_project_json(mut self, crate_roots: Vec<PathBuf>) -> Self {
+        self.project_json_roots = Some(crate_roots);
+        self
+    }
+
     /// Get the data directory path.
     pub fn data_dir(&self) -> PathBuf {
-        self.project_path.join(DATA_DIR)
+        match &self.data_dir_override {
+            Some(dir) => dir.clone(),
+            None => self.project_path.join(DATA_DIR),
+        }
     }
 
     /// Get the cached SCIP binary index path.
@@ -135,6 +275,52 @@ impl ScipCache {
         self.json_path().exists()
     }
 
+    /// Path to the source-freshness manifest.
+    pub fn manifest_path(&self) -> PathBuf {
+        self.data_dir().join(MANIFEST_FILE)
+    }
+
+    /// Whether the cached index is stale relative to the current source tree.
+    ///
+    /// An index is stale if there is no stored manifest, or if the set of
+    /// source files and their content digests differs from the manifest
+    /// (detecting added, removed, and modified files).
+    pub fn is_stale(&self) -> bool {
+        let stored = match std::fs::read_to_string(self.manifest_path()) {
+            Ok(content) => match serde_json::from_str::<BTreeMap<String, String>>(&content) {
+                Ok(map) => map,
+                Err(_) => return true,
+            },
+            Err(_) => return true,
+        };
+        stored != self.build_source_manifest()
+    }
+
+    /// Compute the current source manifest: a sorted map of project-relative
+    /// path to a content digest for each `.rs`/Verus source file plus
+    /// `Cargo.toml`.
+    fn build_source_manifest(&self) -> BTreeMap<String, String> {
+        let mut manifest = BTreeMap::new();
+        collect_source_files(&self.project_path, &self.project_path, &mut manifest);
+
+        let cargo_toml = self.project_path.join("Cargo.toml");
+        if cargo_toml.is_file() {
+            if let Some(digest) = file_digest(&cargo_toml) {
+                manifest.insert("Cargo.toml".to_string(), digest);
+            }
+        }
+
+        manifest
+    }
+
+    /// Write the current source manifest to disk.
+    fn write_manifest(&self) -> Result<(), ScipError> {
+        let manifest = self.build_source_manifest();
+        let json = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| ScipError::DecodeFailed(e.to_string()))?;
+        std::fs::write(self.manifest_path(), json).map_err(ScipError::WriteJsonFailed)
+    }
+
     /// Get the path to the SCIP JSON, generating it if necessary.
     ///
     /// # Arguments
@@ -146,8 +332,9 @@ impl ScipCache {
     pub fn get_or_generate(&self, regenerate: bool, verbose: bool) -> Result<PathBuf, ScipError> {
         let json_path = self.json_path();
 
-        // Use cache if available and not regenerating
-        if json_path.exists() && !regenerate {
+        // Use cache only if it exists, regeneration wasn't requested, and the
+        // source tree hasn't changed since the index was built.
+        if json_path.exists() && !regenerate && !self.is_stale() {
             return Ok(json_path);
         }
 
@@ -160,31 +347,308 @@ impl ScipCache {
         // Convert to JSON
         self.convert_to_json(verbose)?;
 
+        // Record the source manifest so a later call can detect staleness.
+        self.write_manifest()?;
+
+        Ok(json_path)
+    }
+
+    /// Decode the cached `index.scip` into a typed [`scip::types::Index`].
+    ///
+    /// The `index.scip` file is a protobuf-encoded `scip::types::Index`
+    /// message (Metadata + repeated Documents with occurrences and
+    /// symbol_information, plus external_symbols). Decoding it in-process
+    /// avoids shelling out to `scip print` and the extra on-disk JSON file,
+    /// and hands typed documents/occurrences straight to callers.
+    pub fn load_index(&self) -> Result<Index, ScipError> {
+        let bytes = std::fs::read(self.scip_path()).map_err(ScipError::ReadIndexFailed)?;
+        Index::decode(&bytes[..]).map_err(|e| ScipError::DecodeFailed(e.to_string()))
+    }
+
+    /// Ensure `index.scip` exists (generating if necessary) and return the
+    /// decoded index. This is the fast path: it needs only the analyzer, not
+    /// the `scip` CLI, and produces no intermediate JSON.
+    pub fn get_or_generate_index(
+        &self,
+        regenerate: bool,
+        verbose: bool,
+    ) -> Result<Index, ScipError> {
+        if !self.scip_path().exists() || regenerate || self.is_stale() {
+            if !self.analyzer.is_available() {
+                return Err(ScipError::AnalyzerNotFound(self.analyzer.clone()));
+            }
+            self.check_analyzer_version()?;
+            self.generate_scip_index(verbose)?;
+            self.write_manifest()?;
+        }
+        self.load_index()
+    }
+
+    /// Async counterpart of [`Self::get_or_generate`] for driving indexing from
+    /// an async runtime (e.g. indexing a workspace's crates concurrently).
+    ///
+    /// Progress is emitted through `tracing` events. When `progress` is
+    /// supplied, each stderr line from the analyzer (e.g. "Loading ...",
+    /// "Generating SCIP ...") is also forwarded to the channel so a driver can
+    /// render live progress instead of inheriting raw stdio.
+    pub async fn get_or_generate_async(
+        &self,
+        regenerate: bool,
+        progress: Option<mpsc::UnboundedSender<String>>,
+    ) -> Result<PathBuf, ScipError> {
+        let json_path = self.json_path();
+
+        if json_path.exists() && !regenerate && !self.is_stale() {
+            return Ok(json_path);
+        }
+
+        self.check_prerequisites()?;
+        self.generate_scip_index_async(progress).await?;
+        self.convert_to_json_async().await?;
+        self.write_manifest()?;
+
         Ok(json_path)
     }
 
+    /// Generate the SCIP index using the configured analyzer on the async
+    /// runtime, streaming the analyzer's stderr lines to `progress`.
+    async fn generate_scip_index_async(
+        &self,
+        progress: Option<mpsc::UnboundedSender<String>>,
+    ) -> Result<(), ScipError> {
+        tracing::info!(
+            project = %self.project_path.display(),
+            analyzer = %self.analyzer,
+            "generating SCIP index"
+        );
+
+        self.ensure_project_json(false)?;
+
+        let mut child = tokio::process::Command::new(self.analyzer.program())
+            .args(["scip", "."])
+            .current_dir(&self.project_path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| ScipError::AnalyzerFailed(self.analyzer.clone(), e.to_string()))?;
+
+        if let Some(stderr) = child.stderr.take() {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                tracing::debug!(analyzer = %self.analyzer, "{}", line);
+                if let Some(tx) = &progress {
+                    // A closed receiver just means the driver stopped listening.
+                    let _ = tx.send(line);
+                }
+            }
+        }
+
+        let status = child
+            .wait()
+            .await
+            .map_err(|e| ScipError::AnalyzerFailed(self.analyzer.clone(), e.to_string()))?;
+        if !status.success() {
+            return Err(ScipError::AnalyzerFailed(
+                self.analyzer.clone(),
+                format!("exit status: {}", status),
+            ));
+        }
+
+        let generated_path = self.project_path.join("index.scip");
+        if !generated_path.exists() {
+            return Err(ScipError::IndexNotGenerated(self.analyzer.clone()));
+        }
+
+        let data_dir = self.data_dir();
+        if !data_dir.exists() {
+            tokio::fs::create_dir_all(&data_dir)
+                .await
+                .map_err(ScipError::CreateDirFailed)?;
+        }
+
+        let cached_path = self.scip_path();
+        tokio::fs::rename(&generated_path, &cached_path)
+            .await
+            .map_err(ScipError::MoveFileFailed)?;
+
+        tracing::info!(path = %cached_path.display(), "saved index.scip");
+
+        Ok(())
+    }
+
+    /// Convert the SCIP index to JSON format on the async runtime.
+    async fn convert_to_json_async(&self) -> Result<(), ScipError> {
+        tracing::info!("converting index.scip to JSON");
+
+        let scip_path = self.scip_path();
+        let output = tokio::process::Command::new("scip")
+            .args(["print", "--json", scip_path.to_str().unwrap()])
+            .output()
+            .await
+            .map_err(|e| ScipError::ScipPrintFailed(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(ScipError::ScipPrintFailed(format!(
+                "exit status: {}",
+                output.status
+            )));
+        }
+
+        let json_path = self.json_path();
+        tokio::fs::write(&json_path, output.stdout)
+            .await
+            .map_err(ScipError::WriteJsonFailed)?;
+
+        tracing::info!(path = %json_path.display(), "saved SCIP JSON");
+
+        Ok(())
+    }
+
+    /// Synthesize a `rust-project.json` in the project root when needed, so the
+    /// analyzer has an explicit crate graph to index.
+    ///
+    /// Generates the descriptor when explicit crate roots were configured via
+    /// [`Self::with_project_json`], or automatically when the project has no
+    /// `Cargo.toml` and no existing `rust-project.json`.
+    fn ensure_project_json(&self, verbose: bool) -> Result<(), ScipError> {
+        let has_cargo = self.project_path.join("Cargo.toml").exists();
+        let existing = self.project_path.join("rust-project.json");
+
+        let roots = match &self.project_json_roots {
+            Some(roots) => roots.clone(),
+            None => {
+                if has_cargo || existing.exists() {
+                    return Ok(());
+                }
+                self.discover_crate_roots()
+            }
+        };
+
+        if roots.is_empty() {
+            return Ok(());
+        }
+
+        // Without a manifest there are no declared edges between the loose
+        // crate roots, so make every crate depend on all the others. That
+        // over-approximation is what lets the analyzer resolve cross-crate
+        // references (the reason this descriptor is synthesized at all) instead
+        // of emitting an empty, disconnected crate graph.
+        let names: Vec<String> = roots
+            .iter()
+            .enumerate()
+            .map(|(i, root)| crate_name_for(root, i))
+            .collect();
+
+        let descriptor = RustProject {
+            sysroot_src: detect_sysroot_src(),
+            crates: roots
+                .iter()
+                .enumerate()
+                .map(|(i, root)| CrateDef {
+                    root_module: root.to_string_lossy().to_string(),
+                    edition: "2021".to_string(),
+                    deps: names
+                        .iter()
+                        .enumerate()
+                        .filter(|(j, _)| *j != i)
+                        .map(|(j, name)| CrateDep {
+                            krate: j,
+                            name: name.clone(),
+                        })
+                        .collect(),
+                    // Match the analyzer's own defaults for a checked build so
+                    // `#[cfg(test)]`/`debug_assertions` items are indexed.
+                    cfg: vec!["test".to_string(), "debug_assertions".to_string()],
+                })
+                .collect(),
+        };
+
+        let json = serde_json::to_string_pretty(&descriptor)
+            .map_err(|e| ScipError::DecodeFailed(e.to_string()))?;
+        std::fs::write(&existing, json).map_err(ScipError::WriteJsonFailed)?;
+
+        if verbose {
+            tracing::info!(
+                crates = descriptor.crates.len(),
+                "wrote rust-project.json"
+            );
+        }
+        Ok(())
+    }
+
+    /// Discover crate roots for a non-Cargo project: prefer conventional
+    /// `src/lib.rs`/`src/main.rs` and top-level `lib.rs`/`main.rs`, else treat
+    /// each top-level `.rs` file as a crate root.
+    fn discover_crate_roots(&self) -> Vec<PathBuf> {
+        let mut roots = Vec::new();
+        for candidate in ["src/lib.rs", "src/main.rs", "lib.rs", "main.rs"] {
+            let path = self.project_path.join(candidate);
+            if path.is_file() {
+                roots.push(path);
+            }
+        }
+        if !roots.is_empty() {
+            return roots;
+        }
+
+        if let Ok(entries) = std::fs::read_dir(&self.project_path) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().and_then(|e| e.to_str()) == Some("rs") {
+                    roots.push(path);
+                }
+            }
+        }
+        roots.sort();
+        roots
+    }
+
     /// Check that required external tools are available.
+    ///
+    /// Checks the analyzer (presence and version) and the `scip` CLI, which the
+    /// JSON path needs for [`Self::convert_to_json`]. The decoded-index path,
+    /// [`Self::get_or_generate_index`], does not call this and needs only the
+    /// analyzer.
     fn check_prerequisites(&self) -> Result<(), ScipError> {
-        if !command_exists(self.analyzer.command_name()) {
-            return Err(ScipError::AnalyzerNotFound(self.analyzer));
+        if !self.analyzer.is_available() {
+            return Err(ScipError::AnalyzerNotFound(self.analyzer.clone()));
         }
-        if !command_exists("scip") {
+        self.check_analyzer_version()?;
+        if find_in_path("scip").is_none() {
             return Err(ScipError::ScipCliNotFound);
         }
         Ok(())
     }
 
+    /// Verify the analyzer is new enough to emit the SCIP schema this crate
+    /// decodes, by probing `--version`. A version that can't be parsed is
+    /// treated as acceptable (the tool may predate a `--version` flag) so we
+    /// don't reject an otherwise-working analyzer on an unexpected banner.
+    fn check_analyzer_version(&self) -> Result<(), ScipError> {
+        let required = self.analyzer.kind().min_version();
+        if let Some(found) = probe_version(&self.analyzer.program()) {
+            if found < required {
+                return Err(ScipError::AnalyzerTooOld {
+                    found: version_string(found),
+                    required: version_string(required),
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Generate the SCIP index using the configured analyzer.
     fn generate_scip_index(&self, verbose: bool) -> Result<(), ScipError> {
-        if verbose {
-            println!(
-                "Generating SCIP index for {} (using {})...",
-                self.project_path.display(),
-                self.analyzer
-            );
-        }
+        tracing::info!(
+            project = %self.project_path.display(),
+            analyzer = %self.analyzer,
+            "generating SCIP index"
+        );
+
+        // Give the analyzer an explicit crate graph when there's no Cargo.toml.
+        self.ensure_project_json(verbose)?;
 
-        let status = Command::new(self.analyzer.command_name())
+        let status = Command::new(self.analyzer.program())
             .args(["scip", "."])
             .current_dir(&self.project_path)
             .stdout(if verbose {
@@ -203,19 +667,19 @@ impl ScipCache {
             Ok(s) if s.success() => {}
             Ok(s) => {
                 return Err(ScipError::AnalyzerFailed(
-                    self.analyzer,
+                    self.analyzer.clone(),
                     format!("exit status: {}", s),
                 ));
             }
             Err(e) => {
-                return Err(ScipError::AnalyzerFailed(self.analyzer, e.to_string()));
+                return Err(ScipError::AnalyzerFailed(self.analyzer.clone(), e.to_string()));
             }
         }
 
         // Check that index.scip was generated
         let generated_path = self.project_path.join("index.scip");
         if !generated_path.exists() {
-            return Err(ScipError::IndexNotGenerated(self.analyzer));
+            return Err(ScipError::IndexNotGenerated(self.analyzer.clone()));
         }
 
         // Ensure data directory exists
@@ -228,18 +692,14 @@ impl ScipCache {
         let cached_path = self.scip_path();
         std::fs::rename(&generated_path, &cached_path).map_err(ScipError::MoveFileFailed)?;
 
-        if verbose {
-            println!("  Saved index.scip to {}", cached_path.display());
-        }
+        tracing::info!(path = %cached_path.display(), "saved index.scip");
 
         Ok(())
     }
 
     /// Convert the SCIP index to JSON format.
-    fn convert_to_json(&self, verbose: bool) -> Result<(), ScipError> {
-        if verbose {
-            println!("Converting index.scip to JSON...");
-        }
+    fn convert_to_json(&self, _verbose: bool) -> Result<(), ScipError> {
+        tracing::info!("converting index.scip to JSON");
 
         let scip_path = self.scip_path();
         let output = Command::new("scip")
@@ -251,9 +711,7 @@ impl ScipCache {
                 let json_path = self.json_path();
                 std::fs::write(&json_path, o.stdout).map_err(ScipError::WriteJsonFailed)?;
 
-                if verbose {
-                    println!("  Saved SCIP JSON to {}", json_path.display());
-                }
+                tracing::info!(path = %json_path.display(), "saved SCIP JSON");
 
                 Ok(())
             }
@@ -269,21 +727,349 @@ impl ScipCache {
     pub fn generation_reason(&self, regenerate: bool) -> &'static str {
         if regenerate {
             "(regeneration requested)"
+        } else if self.has_cached_json() && self.is_stale() {
+            "(source files changed)"
         } else {
             "(no existing SCIP data found)"
         }
     }
 }
 
-/// Check if a command exists in PATH.
-fn command_exists(cmd: &str) -> bool {
-    Command::new("which")
-        .arg(cmd)
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status()
-        .map(|s| s.success())
-        .unwrap_or(false)
+/// Workspace-aware indexer over a Cargo workspace's member crates.
+///
+/// A single [`ScipCache`] is tied to one `project_path` and one `index.scip`,
+/// which forces a multi-crate workspace to be indexed as a whole. `ScipWorkspace`
+/// discovers the workspace members from the root `Cargo.toml`, generates a
+/// separate index per member under `data/<member>/index.scip`, and resolves a
+/// symbol or file to the owning member's index — analogous to selecting which
+/// workspace member to operate on.
+pub struct ScipWorkspace {
+    root: PathBuf,
+    analyzer: Analyzer,
+    /// Member crate directories, relative to `root`, in discovery order.
+    members: Vec<PathBuf>,
+}
+
+impl ScipWorkspace {
+    /// Discover a workspace rooted at `root` using the default verus-analyzer.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self::with_analyzer(root, Analyzer::verus_analyzer())
+    }
+
+    /// Discover a workspace rooted at `root` with a specific analyzer choice.
+    pub fn with_analyzer(root: impl Into<PathBuf>, analyzer: Analyzer) -> Self {
+        let root = root.into();
+        let members = discover_members(&root);
+        Self {
+            root,
+            analyzer,
+            members,
+        }
+    }
+
+    /// The discovered member crate directories, relative to the workspace root.
+    pub fn members(&self) -> &[PathBuf] {
+        &self.members
+    }
+
+    /// Build the [`ScipCache`] for a single member, caching its index under
+    /// `data/<member>/` at the workspace root.
+    fn cache_for(&self, member: &Path) -> ScipCache {
+        let data_dir = self.root.join(DATA_DIR).join(member);
+        ScipCache::with_analyzer(self.root.join(member), self.analyzer.clone()).with_data_dir(data_dir)
+    }
+
+    /// Generate (or reuse) the SCIP index for every member crate.
+    ///
+    /// Generation of individual members that fail is recorded and surfaced
+    /// rather than aborting the whole workspace; the first error is returned
+    /// after all members have been attempted.
+    pub fn generate_all(&self, regenerate: bool, verbose: bool) -> Result<(), ScipError> {
+        let mut first_error = None;
+        for member in &self.members {
+            let cache = self.cache_for(member);
+            if let Err(e) = cache.get_or_generate_index(regenerate, verbose) {
+                tracing::warn!(member = %member.display(), error = %e, "member indexing failed");
+                first_error.get_or_insert(e);
+            }
+        }
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Resolve the member crate that owns `path` and return its [`ScipCache`].
+    ///
+    /// `path` may be absolute or relative to the workspace root; the member
+    /// with the longest matching directory prefix wins, so nested members are
+    /// preferred over their parents.
+    pub fn index_for(&self, path: impl AsRef<Path>) -> Option<ScipCache> {
+        let path = path.as_ref();
+        let rel = path.strip_prefix(&self.root).unwrap_or(path);
+
+        self.members
+            .iter()
+            .filter(|member| rel.starts_with(member))
+            .max_by_key(|member| member.components().count())
+            .map(|member| self.cache_for(member))
+    }
+}
+
+/// Parse the `[workspace] members`/`exclude` globs from the root `Cargo.toml`
+/// and expand them into member directories (those containing a `Cargo.toml`),
+/// relative to `root`. Returns an empty list when there is no workspace table.
+fn discover_members(root: &Path) -> Vec<PathBuf> {
+    let manifest_path = root.join("Cargo.toml");
+    let content = match std::fs::read_to_string(&manifest_path) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+    let manifest: CargoManifest = match toml::from_str(&content) {
+        Ok(manifest) => manifest,
+        Err(_) => return Vec::new(),
+    };
+    let workspace = match manifest.workspace {
+        Some(workspace) => workspace,
+        None => return Vec::new(),
+    };
+
+    let excluded: Vec<PathBuf> = workspace
+        .exclude
+        .iter()
+        .flat_map(|pattern| expand_member_glob(root, pattern))
+        .collect();
+
+    let mut members = Vec::new();
+    for pattern in &workspace.members {
+        for member in expand_member_glob(root, pattern) {
+            if excluded.contains(&member) || members.contains(&member) {
+                continue;
+            }
+            if root.join(&member).join("Cargo.toml").is_file() {
+                members.push(member);
+            }
+        }
+    }
+    members
+}
+
+/// Expand one `members`/`exclude` glob pattern into workspace-relative member
+/// directories. A pattern without glob metacharacters resolves to itself.
+fn expand_member_glob(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let full = root.join(pattern);
+    let mut matched = Vec::new();
+    if let Ok(paths) = glob::glob(&full.to_string_lossy()) {
+        for path in paths.flatten() {
+            if path.is_dir() {
+                if let Ok(rel) = path.strip_prefix(root) {
+                    matched.push(rel.to_path_buf());
+                }
+            }
+        }
+    }
+    if matched.is_empty() && !pattern.contains(['*', '?', '[']) {
+        matched.push(PathBuf::from(pattern));
+    }
+    matched.sort();
+    matched
+}
+
+/// Minimal view of a `Cargo.toml` used to discover workspace members.
+#[derive(Debug, Deserialize)]
+struct CargoManifest {
+    workspace: Option<WorkspaceSection>,
+}
+
+/// The `[workspace]` table's member/exclude globs.
+#[derive(Debug, Deserialize)]
+struct WorkspaceSection {
+    #[serde(default)]
+    members: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+}
+
+/// A synthesized `rust-project.json` descriptor (the non-Cargo project format
+/// that rust-analyzer/verus-analyzer consume).
+#[derive(Debug, Serialize)]
+struct RustProject {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sysroot_src: Option<String>,
+    crates: Vec<CrateDef>,
+}
+
+/// A single crate entry in a `rust-project.json`.
+#[derive(Debug, Serialize)]
+struct CrateDef {
+    root_module: String,
+    edition: String,
+    deps: Vec<CrateDep>,
+    cfg: Vec<String>,
+}
+
+/// A dependency edge between crates in a `rust-project.json`.
+#[derive(Debug, Serialize)]
+struct CrateDep {
+    #[serde(rename = "crate")]
+    krate: usize,
+    name: String,
+}
+
+/// Derive a crate name for a `rust-project.json` entry from its root module
+/// path: the enclosing directory for a conventional root (`lib.rs`/`main.rs`/
+/// `mod.rs`), otherwise the file stem, falling back to `crate<index>`.
+fn crate_name_for(root: &Path, index: usize) -> String {
+    let stem = root.file_stem().and_then(|s| s.to_str());
+    let name = match stem {
+        Some("lib") | Some("main") | Some("mod") => root
+            .parent()
+            .and_then(|p| p.file_name())
+            .and_then(|n| n.to_str()),
+        other => other,
+    };
+    name.map(|n| n.replace('-', "_"))
+        .unwrap_or_else(|| format!("crate{}", index))
+}
+
+/// Locate the toolchain's `library` source directory for `sysroot_src`, via
+/// `rustc --print sysroot`. Returns `None` if rustc is unavailable.
+fn detect_sysroot_src() -> Option<String> {
+    let output = Command::new("rustc").arg("--print").arg("sysroot").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let sysroot = String::from_utf8(output.stdout).ok()?;
+    let lib = PathBuf::from(sysroot.trim()).join("lib/rustlib/src/rust/library");
+    Some(lib.to_string_lossy().to_string())
+}
+
+/// Recursively collect `.rs`/Verus source files under `dir`, keyed by their
+/// path relative to `root`, mapping each to a content digest.
+fn collect_source_files(root: &Path, dir: &Path, manifest: &mut BTreeMap<String, String>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let file_type = match entry.file_type() {
+            Ok(ft) => ft,
+            Err(_) => continue,
+        };
+        if file_type.is_dir() {
+            // Skip the generated data directory and common build output.
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if name == DATA_DIR || name == "target" || name == ".git" {
+                continue;
+            }
+            collect_source_files(root, &path, manifest);
+        } else if path.extension().and_then(|e| e.to_str()) == Some("rs") {
+            if let (Ok(rel), Some(digest)) = (path.strip_prefix(root), file_digest(&path)) {
+                manifest.insert(rel.to_string_lossy().to_string(), digest);
+            }
+        }
+    }
+}
+
+/// Compute a stable digest for a file: a fast blake3 content hash, falling
+/// back to `mtime+size` when the file cannot be read.
+fn file_digest(path: &Path) -> Option<String> {
+    match std::fs::read(path) {
+        Ok(bytes) => Some(blake3::hash(&bytes).to_hex().to_string()),
+        Err(_) => {
+            let meta = std::fs::metadata(path).ok()?;
+            let mtime = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            Some(format!("{}:{}", mtime, meta.len()))
+        }
+    }
+}
+
+/// Locate `cmd` by walking `PATH`, honoring `PATHEXT`/`.exe` on Windows.
+///
+/// Returns the first matching executable path, or `None` if the command isn't
+/// found. This replaces shelling out to `which`, which is absent on Windows.
+fn find_in_path(cmd: &str) -> Option<PathBuf> {
+    // An explicit path component means "use this, don't search PATH".
+    let as_path = Path::new(cmd);
+    if as_path.components().count() > 1 {
+        return as_path.is_file().then(|| as_path.to_path_buf());
+    }
+
+    let path_var = std::env::var_os("PATH")?;
+    for dir in std::env::split_paths(&path_var) {
+        for ext in executable_extensions() {
+            let candidate = if ext.is_empty() {
+                dir.join(cmd)
+            } else {
+                dir.join(format!("{}{}", cmd, ext))
+            };
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+    }
+    None
+}
+
+/// Executable suffixes to try when resolving a command name. On Windows this is
+/// derived from `PATHEXT`; elsewhere it's just the bare name.
+fn executable_extensions() -> Vec<String> {
+    #[cfg(windows)]
+    {
+        match std::env::var("PATHEXT") {
+            Ok(pathext) => pathext
+                .split(';')
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect(),
+            Err(_) => vec![
+                ".EXE".to_string(),
+                ".CMD".to_string(),
+                ".BAT".to_string(),
+                ".COM".to_string(),
+            ],
+        }
+    }
+    #[cfg(not(windows))]
+    {
+        vec![String::new()]
+    }
+}
+
+/// Probe a tool's `--version` output and parse a `major.minor.patch` triple.
+fn probe_version(program: &Path) -> Option<(u64, u64, u64)> {
+    let output = Command::new(program).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    parse_version(&text)
+}
+
+/// Extract the first `major.minor.patch` version triple from `text`.
+fn parse_version(text: &str) -> Option<(u64, u64, u64)> {
+    text.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .find_map(parse_triple)
+}
+
+/// Parse a single `major.minor.patch` token, or `None` if it isn't one.
+fn parse_triple(token: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = token.split('.');
+    let major = parts.next()?.parse::<u64>().ok()?;
+    let minor = parts.next()?.parse::<u64>().ok()?;
+    let patch = parts.next()?.parse::<u64>().ok()?;
+    Some((major, minor, patch))
+}
+
+/// Render a version triple as `major.minor.patch`.
+fn version_string(version: (u64, u64, u64)) -> String {
+    format!("{}.{}.{}", version.0, version.1, version.2)
 }
 
 #[cfg(test)]
@@ -306,20 +1092,113 @@ mod tests {
 
     #[test]
     fn test_scip_error_display() {
-        let err = ScipError::AnalyzerNotFound(Analyzer::VerusAnalyzer);
+        let err = ScipError::AnalyzerNotFound(Analyzer::verus_analyzer());
         assert_eq!(err.to_string(), "verus-analyzer not found in PATH");
 
-        let err = ScipError::AnalyzerNotFound(Analyzer::RustAnalyzer);
+        let err = ScipError::AnalyzerNotFound(Analyzer::rust_analyzer());
         assert_eq!(err.to_string(), "rust-analyzer not found in PATH");
 
         let err = ScipError::ScipCliNotFound;
         assert_eq!(err.to_string(), "scip not found in PATH");
+
+        let err = ScipError::DecodeFailed("bad varint".to_string());
+        assert_eq!(err.to_string(), "failed to decode index.scip: bad varint");
+
+        let err = ScipError::AnalyzerTooOld {
+            found: "0.1.0".to_string(),
+            required: "0.4.0".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "analyzer too old to emit a decodable SCIP index: found 0.1.0, requires >= 0.4.0"
+        );
+    }
+
+    #[test]
+    fn test_manifest_detects_source_changes() {
+        let dir = std::env::temp_dir().join(format!("probe_verus_manifest_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"t\"\n").unwrap();
+        std::fs::write(dir.join("src/lib.rs"), "fn a() {}\n").unwrap();
+
+        let cache = ScipCache::new(&dir);
+        let before = cache.build_source_manifest();
+        assert!(before.contains_key("Cargo.toml"));
+        assert!(before.contains_key("src/lib.rs"));
+
+        // Modifying a source file changes its digest.
+        std::fs::write(dir.join("src/lib.rs"), "fn a() { let _ = 1; }\n").unwrap();
+        let after = cache.build_source_manifest();
+        assert_ne!(before.get("src/lib.rs"), after.get("src/lib.rs"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_workspace_discovers_members() {
+        let dir =
+            std::env::temp_dir().join(format!("probe_verus_workspace_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("crates/alpha")).unwrap();
+        std::fs::create_dir_all(dir.join("crates/beta")).unwrap();
+        std::fs::create_dir_all(dir.join("tools/skip")).unwrap();
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/beta\"]\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("crates/alpha/Cargo.toml"), "[package]\nname=\"a\"\n").unwrap();
+        std::fs::write(dir.join("crates/beta/Cargo.toml"), "[package]\nname=\"b\"\n").unwrap();
+
+        let ws = ScipWorkspace::new(&dir);
+        assert_eq!(ws.members(), &[PathBuf::from("crates/alpha")]);
+
+        // A file under a member resolves to that member's cache.
+        let cache = ws.index_for(dir.join("crates/alpha/src/lib.rs")).unwrap();
+        assert_eq!(
+            cache.data_dir(),
+            dir.join("data").join("crates/alpha")
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
     }
 
     #[test]
     fn test_scip_cache_with_analyzer() {
-        let cache = ScipCache::with_analyzer("/path/to/project", Analyzer::RustAnalyzer);
-        assert_eq!(cache.analyzer, Analyzer::RustAnalyzer);
+        let cache = ScipCache::with_analyzer("/path/to/project", Analyzer::rust_analyzer());
+        assert_eq!(cache.analyzer, Analyzer::rust_analyzer());
         assert_eq!(cache.data_dir(), PathBuf::from("/path/to/project/data"));
     }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("verus-analyzer 0.4.1"), Some((0, 4, 1)));
+        assert_eq!(parse_version("scip 1.12.0\n"), Some((1, 12, 0)));
+        assert_eq!(
+            parse_version("rust-analyzer 0.3.2024-standalone"),
+            Some((0, 3, 2024))
+        );
+        assert_eq!(parse_version("no version here"), None);
+    }
+
+    #[test]
+    fn test_version_gate_is_per_kind() {
+        // Standalone rust-analyzer reports a `0.3.xxxx-standalone` scheme, which
+        // must satisfy rust-analyzer's floor but would fail verus-analyzer's.
+        let standalone = parse_version("rust-analyzer 0.3.2024-standalone").unwrap();
+        assert!(standalone >= AnalyzerKind::RustAnalyzer.min_version());
+        assert!(standalone < AnalyzerKind::VerusAnalyzer.min_version());
+
+        // A genuinely ancient analyzer is below both floors.
+        let ancient = parse_version("verus-analyzer 0.1.0").unwrap();
+        assert!(ancient < AnalyzerKind::VerusAnalyzer.min_version());
+        assert!(ancient < AnalyzerKind::RustAnalyzer.min_version());
+    }
+
+    #[test]
+    fn test_analyzer_binary_override() {
+        let analyzer = Analyzer::rust_analyzer().with_binary("/opt/bin/rust-analyzer");
+        assert_eq!(analyzer.program(), PathBuf::from("/opt/bin/rust-analyzer"));
+        // The diagnostic name still reflects the kind.
+        assert_eq!(analyzer.command_name(), "rust-analyzer");
+    }
 }
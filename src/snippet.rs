@@ -0,0 +1,223 @@
+//! Compiler-style source-snippet rendering for diagnostics.
+//!
+//! Modeled on the `annotate-snippets` approach: callers build a [`Snippet`]
+//! from a title and a list of [`Annotation`]s (file path + span + label), and
+//! the renderer reads each referenced file once, computes 1-based line numbers,
+//! and prints a gutter with line numbers, the source line, and a `^^^^`
+//! underline spanning the annotated columns.
+//!
+//! Used by the atomize command to turn duplicate `code_name` collisions into
+//! an actionable, compiler-style error instead of a bare text list.
+
+use std::fmt::Write as _;
+
+/// A single annotation pointing at a source location.
+#[derive(Debug, Clone)]
+pub struct Annotation {
+    /// Path to the source file.
+    pub file: String,
+    /// 1-based line number of the annotated location.
+    pub line: usize,
+    /// Optional 1-based `(start_col, end_col)` to underline. When absent, the
+    /// whole line is underlined (e.g. spans that only carry `lines_start`).
+    pub columns: Option<(usize, usize)>,
+    /// Label printed next to the underline.
+    pub label: String,
+}
+
+/// A group of annotations sharing one title.
+#[derive(Debug, Clone)]
+pub struct Snippet {
+    pub title: String,
+    pub annotations: Vec<Annotation>,
+}
+
+impl Snippet {
+    /// Render the snippet to a string.
+    ///
+    /// * `color` enables ANSI color; when false a plain fallback is produced.
+    /// * `term_width` caps source-line display width.
+    ///
+    /// Annotations in the same file are merged into a single snippet block in
+    /// line order. Files that cannot be read degrade to a plain-text line of
+    /// `path:line: label`, matching the old bare-list behavior.
+    pub fn render(&self, color: bool, term_width: usize) -> String {
+        let mut out = String::new();
+        let (title_style, reset) = if color {
+            ("\x1b[1;31m", "\x1b[0m")
+        } else {
+            ("", "")
+        };
+        let _ = writeln!(out, "{}error:{} {}", title_style, reset, self.title);
+
+        // Group annotations by file, preserving first-seen order of files.
+        let mut by_file: Vec<(&str, Vec<&Annotation>)> = Vec::new();
+        for ann in &self.annotations {
+            match by_file.iter_mut().find(|(file, _)| *file == ann.file.as_str()) {
+                Some((_, anns)) => anns.push(ann),
+                None => by_file.push((ann.file.as_str(), vec![ann])),
+            }
+        }
+
+        for (file, mut anns) in by_file {
+            anns.sort_by_key(|a| a.line);
+
+            match std::fs::read_to_string(file) {
+                Ok(contents) => {
+                    let lines: Vec<&str> = contents.lines().collect();
+                    let gutter_width = anns
+                        .iter()
+                        .map(|a| a.line.to_string().len())
+                        .max()
+                        .unwrap_or(1);
+
+                    let _ = writeln!(out, "{:>w$}--> {}", "", file, w = gutter_width);
+                    for ann in anns {
+                        render_line(&mut out, &lines, ann, gutter_width, term_width, color);
+                    }
+                }
+                Err(_) => {
+                    // File gone or unreadable: fall back to a plain reference.
+                    for ann in anns {
+                        let _ = writeln!(out, "  {}:{}: {}", file, ann.line, ann.label);
+                    }
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// Render one annotated source line with its underline.
+fn render_line(
+    out: &mut String,
+    lines: &[&str],
+    ann: &Annotation,
+    gutter_width: usize,
+    term_width: usize,
+    color: bool,
+) {
+    let (underline_style, reset) = if color {
+        ("\x1b[1;31m", "\x1b[0m")
+    } else {
+        ("", "")
+    };
+
+    // Line numbers are 1-based; bail to a plain reference if out of range.
+    let Some(src) = ann.line.checked_sub(1).and_then(|i| lines.get(i)) else {
+        let _ = writeln!(out, "  {}: {}", ann.line, ann.label);
+        return;
+    };
+
+    let display: String = if src.len() > term_width {
+        src.chars().take(term_width).collect()
+    } else {
+        src.to_string()
+    };
+
+    let _ = writeln!(out, "{:>w$} | {}", ann.line, display, w = gutter_width);
+
+    // Compute underline span. Columns are 1-based and clamped to the line.
+    let width = display.chars().count().max(1);
+    let (start, end) = match ann.columns {
+        Some((s, e)) => {
+            let s = s.clamp(1, width);
+            (s, e.clamp(s, width))
+        }
+        None => (1, width),
+    };
+    let pad = start.saturating_sub(1);
+    let len = (end - start + 1).max(1);
+    let carets = "^".repeat(len);
+
+    let _ = writeln!(
+        out,
+        "{:>w$} | {}{}{}{} {}",
+        "",
+        " ".repeat(pad),
+        underline_style,
+        carets,
+        reset,
+        ann.label,
+        w = gutter_width,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_file_falls_back_to_plain() {
+        let snippet = Snippet {
+            title: "duplicate code_name 'foo'".to_string(),
+            annotations: vec![Annotation {
+                file: "/nonexistent/path.rs".to_string(),
+                line: 42,
+                columns: None,
+                label: "defined here".to_string(),
+            }],
+        };
+        let rendered = snippet.render(false, 80);
+        assert!(rendered.contains("/nonexistent/path.rs:42: defined here"));
+    }
+
+    #[test]
+    fn test_renders_underline_for_existing_file() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("probe_verus_snippet_test.rs");
+        std::fs::write(&path, "fn a() {}\nfn foo() {}\nfn b() {}\n").unwrap();
+
+        let snippet = Snippet {
+            title: "duplicate code_name 'foo'".to_string(),
+            annotations: vec![Annotation {
+                file: path.to_string_lossy().to_string(),
+                line: 2,
+                columns: Some((4, 6)),
+                label: "defined here".to_string(),
+            }],
+        };
+        let rendered = snippet.render(false, 80);
+        assert!(rendered.contains("fn foo() {}"));
+        assert!(rendered.contains("^^^"));
+        assert!(rendered.contains("defined here"));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_same_file_annotations_merged_in_line_order() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("probe_verus_snippet_merge_test.rs");
+        std::fs::write(&path, "a\nb\nc\nd\n").unwrap();
+        let file = path.to_string_lossy().to_string();
+
+        let snippet = Snippet {
+            title: "duplicate".to_string(),
+            annotations: vec![
+                Annotation {
+                    file: file.clone(),
+                    line: 3,
+                    columns: None,
+                    label: "and here".to_string(),
+                },
+                Annotation {
+                    file: file.clone(),
+                    line: 1,
+                    columns: None,
+                    label: "defined here".to_string(),
+                },
+            ],
+        };
+        let rendered = snippet.render(false, 80);
+        // Only one `-->` header for the single file.
+        assert_eq!(rendered.matches("-->").count(), 1);
+        // Line 1's annotation precedes line 3's.
+        let first = rendered.find("defined here").unwrap();
+        let second = rendered.find("and here").unwrap();
+        assert!(first < second);
+
+        std::fs::remove_file(&path).ok();
+    }
+}